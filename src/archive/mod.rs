@@ -0,0 +1,640 @@
+// Archiving appointments that are past.
+//
+// How the archive folder is organised depends on the `ArchiveLayout`
+// chosen in `rremind config`:
+//
+// - file:      one archive per .rem-file (work.rem -> work.done),
+// - merged:    *one* archive file for everything (rremind.done),
+// - year:      one archive file per year (2025.done),
+// - year/file: one folder per year, one file per .rem-file (2025/work.done).
+//
+// In the `merged` and `year` layouts, lines from different .rem-files
+// share an archive file; each line is then prefixed with the name of the
+// .rem-file it came from, e.g.
+//
+// [work.rem] 2025 May 17 AT 10:00-11:00, Meeting with John Dee
+//
+// `rremind archive <search>` archives one line only (found like in
+// `delete`, e.g. by its id), as long as it has a full date.
+//
+// Every archived line is noted in a journal (rremind.journal in the
+// archive folder), so that `rremind unarchive` can move it back to
+// where it came from.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    Appointment,
+    parser::{as_date, get_tags, get_termin_from_line, split_line},
+    search::Matcher,
+    utils::{get_flag_value, read_lines_from_file, replace_line_in_file, write_lines_to_file},
+};
+
+/// Name of the archive file in merged mode
+pub(crate) const MERGED_ARCHIVE_NAME: &str = "rremind.done";
+
+/// Name of the journal of all archive runs (in the archive folder)
+const JOURNAL_NAME: &str = "rremind.journal";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ArchiveLayout {
+    PerFile,
+    Merged,
+    Year,
+    YearPerFile,
+}
+
+impl ArchiveLayout {
+    pub(crate) fn from_config_value(s_in: &str) -> Option<ArchiveLayout> {
+        match s_in.trim().to_lowercase().as_str() {
+            "file" => Some(ArchiveLayout::PerFile),
+            "merged" => Some(ArchiveLayout::Merged),
+            "year" => Some(ArchiveLayout::Year),
+            "year/file" => Some(ArchiveLayout::YearPerFile),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_config_value(self) -> &'static str {
+        match self {
+            ArchiveLayout::PerFile => "file",
+            ArchiveLayout::Merged => "merged",
+            ArchiveLayout::Year => "year",
+            ArchiveLayout::YearPerFile => "year/file",
+        }
+    }
+
+    // Do lines from several .rem-files end up in the same archive file?
+    fn is_shared(self) -> bool {
+        matches!(self, ArchiveLayout::Merged | ArchiveLayout::Year)
+    }
+
+    // Name of the archive file for an appointment from `source_stem`.rem
+    // (The archive folder is expected to end with a '/', see `rremind config`)
+    fn get_archive_name(
+        self,
+        archiv_folder: &str,
+        source_stem: &str,
+        termin: &Appointment,
+    ) -> String {
+        let year = termin
+            .appointment_date
+            .map(|d| d.year())
+            .unwrap_or_default();
+        match self {
+            ArchiveLayout::PerFile => format!("{archiv_folder}{source_stem}.done"),
+            ArchiveLayout::Merged => format!("{archiv_folder}{MERGED_ARCHIVE_NAME}"),
+            ArchiveLayout::Year => format!("{archiv_folder}{year}.done"),
+            ArchiveLayout::YearPerFile => format!("{archiv_folder}{year}/{source_stem}.done"),
+        }
+    }
+}
+
+/// Archive the past appointments of the .rem-file `file_name`, or only
+/// line `only_line` (1-based) if it has a full date; `timestamp`
+/// identifies this archive run in the journal.
+pub(crate) fn archive_appointments(
+    file_name: &Path,
+    contents: &str,
+    archiv_folder: &str,
+    layout: ArchiveLayout,
+    timestamp: &str,
+    only_line: Option<usize>,
+) {
+    let source_name = file_name.file_name().unwrap().to_str().unwrap();
+    let source_stem = file_name.file_stem().unwrap().to_str().unwrap();
+
+    println!(
+        "\nLooking for items to archive in {}...",
+        file_name.as_os_str().to_str().unwrap()
+    );
+
+    let mut b_found = false;
+    let mut removed = 0; // lines removed from the file so far
+    for (line_no, line) in contents.lines().enumerate() {
+        if only_line.is_some_and(|only| only != line_no + 1) {
+            continue;
+        }
+        if let Some(termin) = get_termin_from_line(line, None)
+            && (termin.is_past() || (only_line.is_some() && termin.appointment_is_full_date))
+        {
+            println!(" - {line}");
+            b_found = true;
+            let archive_name = layout.get_archive_name(archiv_folder, source_stem, &termin);
+            let archive_line = match layout.is_shared() {
+                true => to_shared_archive_line(source_name, line),
+                false => line.to_string(),
+            };
+            if archive_appointment(
+                line,
+                line_no + 1 - removed,
+                &archive_line,
+                file_name,
+                &archive_name,
+            ) {
+                removed += 1;
+                let entry = JournalEntry {
+                    timestamp: timestamp.to_string(),
+                    source_file: file_name.to_string_lossy().to_string(),
+                    line_no,
+                    archive_file: archive_name,
+                    line: line.to_string(),
+                };
+                add_line_to_archive_file(
+                    &format!("{archiv_folder}{JOURNAL_NAME}"),
+                    &entry.to_journal_line(),
+                );
+            }
+        }
+    }
+
+    match (b_found, only_line) {
+        (false, Some(_)) => println!(" - only appointments with a full date can be archived"),
+        (false, None) => println!(" - no items to archive"),
+        _ => {}
+    }
+}
+
+// Move `line`, which is now line `line_no` (1-based) of `file_name`, to
+// the archive. Only this line is removed, not identical ones. If it
+// cannot be removed, it is taken out of the archive again => false
+fn archive_appointment(
+    line: &str,
+    line_no: usize,
+    archive_line: &str,
+    file_name: &Path,
+    archive_name: &str,
+) -> bool {
+    if !add_line_to_archive_file(archive_name, archive_line) {
+        return false;
+    }
+    match replace_line_in_file(file_name.as_os_str().to_str().unwrap(), line_no, line, None) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("{e}");
+            let mut archive_lines = read_lines_from_file(archive_name);
+            if archive_lines.last().map(String::as_str) == Some(archive_line) {
+                archive_lines.pop();
+                write_lines_to_file(archive_name, &archive_lines);
+            }
+            false
+        }
+    }
+}
+
+fn add_line_to_archive_file(archive_name: &str, line: &str) -> bool {
+    if let Some(archive_folder) = Path::new(archive_name).parent() {
+        fs::create_dir_all(archive_folder).unwrap_or_else(|_| {
+            panic!(
+                "Cannot create archive directory `{}`",
+                archive_folder.display()
+            )
+        });
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(archive_name)
+        .unwrap();
+
+    if let Err(e) = writeln!(file, "{}", line) {
+        println!("Couldn't write to file: {}", e);
+        return false;
+    }
+    true
+}
+
+// work.rem, "2025 May 17, x" => "[work.rem] 2025 May 17, x"
+fn to_shared_archive_line(source_name: &str, line: &str) -> String {
+    format!("[{source_name}] {line}")
+}
+
+/// Split a line of an archive file into the name of the
+/// .rem-file it came from (if it is noted in the line) and
+/// the original appointment line.
+pub(crate) fn split_archive_line(line: &str) -> (Option<&str>, &str) {
+    if line.starts_with('[')
+        && let Some((source, rest)) = line[1..].split_once("] ")
+    {
+        return (Some(source), rest);
+    }
+    (None, line)
+}
+
+/// One archived line, as noted in the journal:
+/// timestamp of the archive run, the .rem-file the line came from,
+/// its (0-based) position in that file, the archive file, and the line itself.
+#[derive(Debug, PartialEq)]
+struct JournalEntry {
+    timestamp: String,
+    source_file: String,
+    line_no: usize,
+    archive_file: String,
+    line: String,
+}
+
+impl JournalEntry {
+    // Where the line is in the lines of its archive file: in shared
+    // layouts, the line with the name of its .rem-file (an identical
+    // line from another .rem-file is not it), otherwise the line itself
+    fn find_in_archive(&self, archive_lines: &[String]) -> Option<usize> {
+        let source_name = Path::new(&self.source_file)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let shared_line = to_shared_archive_line(&source_name, &self.line);
+        archive_lines
+            .iter()
+            .position(|archived| *archived == shared_line)
+            .or_else(|| {
+                archive_lines
+                    .iter()
+                    .position(|archived| *archived == self.line)
+            })
+    }
+
+    // Tab separated; the line comes last, so it may contain anything.
+    fn to_journal_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.timestamp, self.source_file, self.line_no, self.archive_file, self.line
+        )
+    }
+
+    fn from_journal_line(s_in: &str) -> Option<JournalEntry> {
+        let mut fields = s_in.splitn(5, '\t');
+        Some(JournalEntry {
+            timestamp: fields.next()?.to_string(),
+            source_file: fields.next()?.to_string(),
+            line_no: fields.next()?.parse::<usize>().ok()?,
+            archive_file: fields.next()?.to_string(),
+            line: fields.next()?.to_string(),
+        })
+    }
+}
+
+/// Which archived lines `rremind unarchive` should move back
+#[derive(Debug)]
+pub(crate) enum UnarchiveSelection {
+    Last,             // --last: everything of the latest archive run
+    Since(NaiveDate), // --since <date>: everything archived on or after <date>
+    Match(Matcher),   // --match <term>: archived lines matching <term>, as in `when_was`
+}
+
+impl UnarchiveSelection {
+    pub(crate) fn from_args(args: &[String]) -> Result<UnarchiveSelection, String> {
+        if let Some(since) = get_flag_value(args, "--since") {
+            return Ok(UnarchiveSelection::Since(parse_range_date(&since, false)?));
+        }
+        if let Some(term) = get_flag_value(args, "--match") {
+            return Ok(UnarchiveSelection::Match(Matcher::from_args(&term, args)?));
+        }
+        Ok(UnarchiveSelection::Last)
+    }
+}
+
+/// Move archived lines back to the .rem-files they came from, at
+/// their original position, and remove them from the archive
+/// and from the journal.
+pub(crate) fn unarchive_appointments(archiv_folder: &str, selection: &UnarchiveSelection) {
+    let journal_name = format!("{archiv_folder}{JOURNAL_NAME}");
+    let journal: Vec<JournalEntry> = read_lines_from_file(&journal_name)
+        .iter()
+        .filter_map(|line| JournalEntry::from_journal_line(line))
+        .collect();
+
+    let last_run = journal.iter().map(|entry| &entry.timestamp).max().cloned();
+    let (mut selected, mut kept): (Vec<JournalEntry>, Vec<JournalEntry>) =
+        journal.into_iter().partition(|entry| match selection {
+            UnarchiveSelection::Last => Some(&entry.timestamp) == last_run.as_ref(),
+            UnarchiveSelection::Since(datum) => {
+                entry.timestamp.as_str() >= datum.format("%Y-%m-%d").to_string().as_str()
+            }
+            UnarchiveSelection::Match(matcher) => {
+                matcher.matches(&entry.line, split_line(&entry.line).1.unwrap_or(""))
+            }
+        });
+
+    if selected.is_empty() {
+        println!("Nothing to unarchive.");
+        return;
+    }
+
+    // Undo the latest run first; within a run, lines go back in
+    // their original order, so that each lands at its old position.
+    selected.sort_by(|e1, e2| {
+        e2.timestamp
+            .cmp(&e1.timestamp)
+            .then(e1.line_no.cmp(&e2.line_no))
+    });
+
+    for entry in selected {
+        let mut archive_lines = read_lines_from_file(&entry.archive_file);
+        let Some(index) = entry.find_in_archive(&archive_lines) else {
+            // Not (or no longer) archived: putting it back would duplicate it
+            println!(
+                " - {} is not in {}; left in the journal",
+                entry.line, entry.archive_file
+            );
+            kept.push(entry);
+            continue;
+        };
+        archive_lines.remove(index);
+        write_lines_to_file(&entry.archive_file, &archive_lines);
+
+        let mut source_lines = read_lines_from_file(&entry.source_file);
+        let index = entry.line_no.min(source_lines.len());
+        source_lines.insert(index, entry.line.to_owned());
+        write_lines_to_file(&entry.source_file, &source_lines);
+
+        println!(" - {} (back to {})", entry.line, entry.source_file);
+    }
+
+    kept.sort_by(|e1, e2| e1.timestamp.cmp(&e2.timestamp));
+    let kept_lines: Vec<String> = kept.iter().map(|entry| entry.to_journal_line()).collect();
+    write_lines_to_file(&journal_name, &kept_lines);
+}
+
+/// Where `when_was` is looking (besides the search term):
+/// `rremind when_was dentist --file work --tag health --since 2023 --until 2024-06-30`
+/// (The search term may be empty, e.g. `rremind when_was --tag health --since 2023`)
+#[derive(Debug)]
+pub(crate) struct ArchiveQuery {
+    pub(crate) file: Option<String>,
+    pub(crate) tag: Option<String>,
+    pub(crate) since: Option<NaiveDate>,
+    pub(crate) until: Option<NaiveDate>,
+}
+
+impl ArchiveQuery {
+    pub(crate) fn from_args(args: &[String]) -> Result<ArchiveQuery, String> {
+        Ok(ArchiveQuery {
+            file: get_flag_value(args, "--file"),
+            tag: get_flag_value(args, "--tag")
+                .map(|tag| tag.trim_start_matches('#').to_lowercase()),
+            since: get_flag_value(args, "--since")
+                .map(|s| parse_range_date(&s, false))
+                .transpose()?,
+            until: get_flag_value(args, "--until")
+                .map(|s| parse_range_date(&s, true))
+                .transpose()?,
+        })
+    }
+
+    fn matches_year(&self, year: i32) -> bool {
+        self.since.is_none_or(|since| since.year() <= year)
+            && self.until.is_none_or(|until| until.year() >= year)
+    }
+
+    fn matches_file(&self, source: &str) -> bool {
+        self.file
+            .as_ref()
+            .is_none_or(|file| source == file || source.trim_end_matches(".rem") == file)
+    }
+
+    fn matches_tag(&self, termin: &Appointment) -> bool {
+        self.tag
+            .as_ref()
+            .is_none_or(|tag| get_tags(&termin.appointment_description).contains(tag))
+    }
+
+    fn matches_date(&self, datum: Option<NaiveDate>) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        match datum {
+            Some(d) => {
+                self.since.is_none_or(|since| since <= d)
+                    && self.until.is_none_or(|until| until >= d)
+            }
+            None => false,
+        }
+    }
+}
+
+// Dates for --since and --until are ISO, German, or a year only:
+// "--since 2023" is Jan 1, 2023, "--until 2023" is Dec 31, 2023
+fn parse_range_date(s_in: &str, is_end: bool) -> Result<NaiveDate, String> {
+    if let Some(datum) = as_date(s_in) {
+        return Ok(datum);
+    }
+    if let Ok(year) = s_in.parse::<i32>() {
+        let datum = match is_end {
+            true => NaiveDate::from_ymd_opt(year, 12, 31),
+            false => NaiveDate::from_ymd_opt(year, 1, 1),
+        };
+        if let Some(d) = datum {
+            return Ok(d);
+        }
+    }
+    Err(format!(
+        "Sorry, `{s_in}` is not a date I understand (try 2025-3-10, 10.3.2025, or 2025)."
+    ))
+}
+
+/// All archive files that may contain appointments the query is
+/// looking for: files and folders named after a year (2025.done,
+/// 2025/work.done) are only read if the year is in the requested range.
+pub(crate) fn get_archive_files(archiv_folder: &str, query: &ArchiveQuery) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = vec![];
+    let dir = fs::read_dir(archiv_folder).unwrap_or_else(|_| {
+        panic!(
+            "Cannot find folder >{archiv_folder}<, which is supposedly containing the reminder archive. Try `rremind config`?"
+        )
+    });
+
+    for datei in dir.flatten() {
+        let path = datei.path();
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let year = stem.parse::<i32>().ok();
+        if year.is_some_and(|y| !query.matches_year(y)) {
+            continue;
+        }
+        if path.is_dir() {
+            if year.is_some() {
+                for year_datei in fs::read_dir(&path).into_iter().flatten().flatten() {
+                    if year_datei.path().to_string_lossy().ends_with(".done") {
+                        files.push(year_datei.path());
+                    }
+                }
+            }
+        } else if path.to_string_lossy().ends_with("done") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Like `accumulate_termine_by_search`, but for the lines of an
+/// archive file: the source file is stored in the appointment so that
+/// it can be shown, and the query's filters are applied.
+///
+/// `archive_name` is the name of the archive file; unless the line
+/// notes its source, the source is derived from it (work.done -> work.rem).
+pub(crate) fn accumulate_archived_termine_by_search(
+    matcher: &Matcher,
+    query: &ArchiveQuery,
+    archive_name: &str,
+    termine_aus_datei: &str,
+    termine: &mut Vec<Appointment>,
+) {
+    let default_source = format!("{}.rem", archive_name.trim_end_matches(".done"));
+    for archived_line in termine_aus_datei.lines() {
+        let (source, line) = split_archive_line(archived_line);
+        let source = source.unwrap_or(&default_source);
+        if !query.matches_file(source) {
+            continue;
+        }
+        if let Some(mut found) = get_termin_from_line(line, None)
+            && matcher.matches(line, &found.appointment_description)
+            && query.matches_date(found.appointment_date)
+            && query.matches_tag(&found)
+        {
+            found.appointment_file = Some(source.to_string());
+            termine.push(found);
+        }
+    }
+}
+
+/// `rremind when_was ... --summary`: how many appointments were
+/// found, and how they are distributed over files and months.
+pub(crate) fn print_archive_summary(termine: &[Appointment]) {
+    println!("\n{} appointment(s) found", termine.len());
+    let first = termine.iter().filter_map(|t| t.appointment_date).min();
+    let last = termine.iter().filter_map(|t| t.appointment_date).max();
+    if let (Some(first), Some(last)) = (first, last) {
+        println!("between {first} and {last}");
+    }
+
+    let mut by_file: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut by_month: BTreeMap<String, usize> = BTreeMap::new();
+    for t in termine {
+        *by_file
+            .entry(t.appointment_file.as_deref().unwrap_or("?"))
+            .or_default() += 1;
+        if let Some(dtm) = t.appointment_date {
+            *by_month.entry(dtm.format("%Y-%m").to_string()).or_default() += 1;
+        }
+    }
+
+    println!("\n  per file\n  ================");
+    for (file, count) in by_file {
+        println!("- {file}: {count}");
+    }
+    println!("\n  per month\n  ================");
+    for (month, count) in by_month {
+        println!("- {month}: {count}");
+    }
+}
+
+#[cfg(test)]
+mod test_archive {
+    use chrono::NaiveDate;
+
+    use crate::archive::{
+        ArchiveLayout, ArchiveQuery, JournalEntry, UnarchiveSelection, parse_range_date,
+        split_archive_line,
+    };
+
+    #[test]
+    fn archive_line_with_source() {
+        assert_eq!(
+            split_archive_line("[work.rem] 2025 May 17, dentist"),
+            (Some("work.rem"), "2025 May 17, dentist")
+        );
+        assert_eq!(
+            split_archive_line("2025 May 17, dentist"),
+            (None, "2025 May 17, dentist")
+        );
+    }
+
+    #[test]
+    fn journal_line() {
+        let entry = JournalEntry {
+            timestamp: "2025-05-20 08:00:00".to_string(),
+            source_file: "/home/h/rem/work.rem".to_string(),
+            line_no: 3,
+            archive_file: "/home/h/archive/work.done".to_string(),
+            line: "2025 May 17 AT 10:00\t, dentist".to_string(),
+        };
+        assert_eq!(
+            JournalEntry::from_journal_line(&entry.to_journal_line()).as_ref(),
+            Some(&entry)
+        );
+        assert_eq!(JournalEntry::from_journal_line("garbage"), None);
+
+        // the line from work.rem, not the same line from home.rem
+        let lines = |s: &str| s.lines().map(|l| l.to_string()).collect::<Vec<String>>();
+        let merged = lines(
+            "[home.rem] 2025 May 17 AT 10:00\t, dentist\n[work.rem] 2025 May 17 AT 10:00\t, dentist",
+        );
+        assert_eq!(entry.find_in_archive(&merged), Some(1));
+        assert_eq!(entry.find_in_archive(&merged[..1]), None);
+        let per_file = lines("2025 May 17 AT 10:00\t, dentist");
+        assert_eq!(entry.find_in_archive(&per_file), Some(0));
+    }
+
+    #[test]
+    fn unarchive_selection() {
+        let args = |s: &str| s.split(' ').map(|a| a.to_string()).collect::<Vec<String>>();
+        assert!(matches!(
+            UnarchiveSelection::from_args(&args("rremind unarchive --since 2025")),
+            Ok(UnarchiveSelection::Since(datum)) if datum == NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        ));
+        assert!(matches!(
+            UnarchiveSelection::from_args(&args("rremind unarchive")),
+            Ok(UnarchiveSelection::Last)
+        ));
+        match UnarchiveSelection::from_args(&args("rremind unarchive --match DENTIST")) {
+            Ok(UnarchiveSelection::Match(matcher)) => {
+                assert!(matcher.matches("2025-05-17 10:00, dentist", "dentist"))
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(UnarchiveSelection::from_args(&args("rremind unarchive --since garbage")).is_err());
+    }
+
+    #[test]
+    fn archive_layout_config() {
+        for layout in [
+            ArchiveLayout::PerFile,
+            ArchiveLayout::Merged,
+            ArchiveLayout::Year,
+            ArchiveLayout::YearPerFile,
+        ] {
+            assert_eq!(
+                ArchiveLayout::from_config_value(layout.to_config_value()),
+                Some(layout)
+            );
+        }
+        assert_eq!(ArchiveLayout::from_config_value("monthly"), None);
+    }
+
+    #[test]
+    fn archive_query_range() {
+        let query = ArchiveQuery {
+            file: None,
+            tag: None,
+            since: parse_range_date("2023", false).ok(),
+            until: parse_range_date("30.6.2024", true).ok(),
+        };
+        assert!(!query.matches_year(2022));
+        assert!(query.matches_year(2023));
+        assert!(query.matches_year(2024));
+        assert!(!query.matches_year(2025));
+        assert!(query.matches_date(NaiveDate::from_ymd_opt(2023, 1, 1)));
+        assert!(!query.matches_date(NaiveDate::from_ymd_opt(2024, 7, 1)));
+        assert!(!query.matches_date(None));
+        assert!(parse_range_date("garbage", false).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+// `rremind check`: reads all lines of the .rem-files and reports
+// what rremind cannot read, or reads differently than intended:
+//
+// work.rem:3:5: time: cannot read the time `25:00`
+//   Mon 25:00, team meeting
+//   try: 14:00, 14.00, 2pm or 2:30pm
+
+use std::fmt;
+
+use crate::parser::{ParseError, Part, get_time_words, parse_line};
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Problem {
+    file: String,
+    line_no: usize, // 1-based, as in editors
+    column: usize,  // 1-based, in characters
+    part: Part,
+    message: String,
+    suggestion: String,
+    line: String,
+}
+
+impl Problem {
+    /// The same problem in the same line, wherever the line is now
+    pub(crate) fn is_same(&self, other: &Problem) -> bool {
+        self.line == other.line && self.part == other.part && self.message == other.message
+    }
+}
+
+/// The problems in `after` that were not in `before`; each problem of
+/// `before` stands for one problem only, so a second copy of a broken
+/// line is new
+pub(crate) fn get_new_problems<'a>(before: &[Problem], after: &'a [Problem]) -> Vec<&'a Problem> {
+    let mut unmatched: Vec<&Problem> = before.iter().collect();
+    after
+        .iter()
+        .filter(
+            |problem| match unmatched.iter().position(|old| old.is_same(problem)) {
+                Some(index) => {
+                    unmatched.remove(index);
+                    false
+                }
+                None => true,
+            },
+        )
+        .collect()
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}: {}\n  {}\n  try: {}",
+            self.file,
+            self.line_no,
+            self.column,
+            self.part,
+            self.message,
+            self.line,
+            self.suggestion
+        )
+    }
+}
+
+/// Problems in the lines of one .rem-file; comments and empty lines are skipped.
+pub(crate) fn check_file(file: &str, termine_aus_datei: &str) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = vec![];
+    let mut ids: Vec<(String, usize)> = vec![]; // ID: names and their lines
+    for (i, line) in termine_aus_datei.lines().enumerate() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let problem = |e: ParseError| Problem {
+            file: file.to_string(),
+            line_no: i + 1,
+            column: e.column,
+            part: e.part,
+            message: e.message,
+            suggestion: e.suggestion.to_string(),
+            line: line.to_string(),
+        };
+
+        let spec = match parse_line(line) {
+            Ok(spec) => spec,
+            Err(e) => {
+                problems.push(problem(e));
+                continue;
+            }
+        };
+        // Lines that parse, but not as intended
+        if let (Some(start), Some(stop)) = (spec.start, spec.get_stop())
+            && stop < start
+        {
+            let period = get_time_words(line).first().cloned().unwrap_or_default();
+            problems.push(problem(ParseError {
+                column: get_column(line, &period),
+                part: Part::Duration,
+                message: format!(
+                    "ends ({}) before it starts ({})",
+                    stop.format("%H:%M"),
+                    start.format("%H:%M")
+                ),
+                suggestion: "swap the times; appointments past midnight need two lines",
+            }));
+        }
+        // An id names one line only
+        if let Some(id) = &spec.id {
+            match ids.iter().find(|(other, _)| other.eq_ignore_ascii_case(id)) {
+                Some((_, other_line_no)) => problems.push(problem(ParseError {
+                    column: get_column(line, &format!("id:{id}")),
+                    part: Part::Modifier,
+                    message: format!("the id `{id}` is used in line {other_line_no}, too"),
+                    suggestion: "give each line its own ID: ID:dentist-2",
+                })),
+                None => ids.push((id.clone(), i + 1)),
+            }
+        }
+        // Without a comma (or REM or MSG), the whole line is taken as the date
+        if spec.description.is_none() {
+            problems.push(problem(ParseError {
+                column: get_column(line, ""),
+                part: Part::Description,
+                message: "there is no description".to_string(),
+                suggestion: "separate date and description with a comma (or REM or MSG): Mon 10:00, dentist",
+            }));
+        }
+    }
+    problems
+}
+
+// Column of `word` in `line` (1-based, in characters); the end of the
+// line if it is not found
+fn get_column(line: &str, word: &str) -> usize {
+    let small = line.to_lowercase();
+    match word.is_empty() {
+        true => line.chars().count() + 1,
+        false => match small.find(&word.to_lowercase()) {
+            Some(i) => small[..i].chars().count() + 1,
+            None => 1,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test_check {
+    use super::{Part, check_file, get_new_problems};
+
+    fn check(line: &str) -> Vec<(usize, Part)> {
+        check_file("test.rem", line)
+            .iter()
+            .map(|p| (p.column, p.part))
+            .collect()
+    }
+
+    #[test]
+    fn check_lines() {
+        assert!(check("# color=red\n\nMon 10:00, dentist\n2025 may 17, x").is_empty());
+        assert_eq!(check("feb 30, birthday"), vec![(1, Part::Date)]);
+        assert_eq!(check("2025 apr 31, x"), vec![(1, Part::Date)]);
+        assert_eq!(check("30.2.2025, x"), vec![(1, Part::Date)]);
+        assert!(check("feb 29, leap").is_empty());
+        assert_eq!(check("someday, x"), vec![(1, Part::Date)]);
+        assert_eq!(check("Mon 25:00, x"), vec![(5, Part::Time)]);
+        assert_eq!(check("Mon 11:00-10:00, x"), vec![(5, Part::Duration)]);
+        assert_eq!(check("Mon 10:00"), vec![(10, Part::Description)]);
+        assert_eq!(
+            check("Mon ID:gym, gym\nTue id:Gym, gym"),
+            vec![(5, Part::Modifier)]
+        );
+    }
+
+    #[test]
+    fn check_line_numbers() {
+        let problems = check_file("work.rem", "# color=red\nMon 10:00, ok\nMon 25:00, x");
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert_eq!(
+            problems[0].to_string().lines().next(),
+            Some("work.rem:3:5: time: cannot read the time `25:00`")
+        );
+    }
+
+    #[test]
+    fn check_new_problems() {
+        let before = check_file("work.rem", "Mon 25:00, x\nMon 10:00, ok");
+        let after = check_file("work.rem", "Mon 10:00, ok\nMon 25:00, x\nMon 25:00, x");
+        let new = get_new_problems(&before, &after);
+        assert_eq!(new.len(), 1);
+        assert_eq!(
+            new[0].to_string().lines().next(),
+            Some("work.rem:3:5: time: cannot read the time `25:00`")
+        );
+        assert!(get_new_problems(&after, &before).is_empty());
+    }
+}
@@ -0,0 +1,460 @@
+// `rremind delete <search>`, `rremind move <search> <new date/time>` and
+// `rremind edit <search>`: change one line of a .rem-file.
+//
+// The line is found like `when` finds appointments (in the description,
+// ignoring case; --regex, --fuzzy, --case-sensitive, --whole-line), by
+// its place: `work.rem:3` (or `work:3`) is the third line of work.rem,
+// or by its id (`id:3f2a9c01`, see ids/mod.rs). If several lines match,
+// the user picks one. The change is shown and has to be confirmed
+// (unless --yes); then the file is rewritten through a temporary file.
+//
+// `rremind open <search|file:line>` finds the line the same way and opens
+// it in $VISUAL or $EDITOR; afterwards, the file is checked again.
+//
+//   rremind move dentist next fri 10:00   =>  17.05.2025 10:00, dentist
+//                                             becomes 23.05.2025 10:00, dentist
+//   rremind move standup 9:30             =>  only the time changes
+
+use std::{fmt, path::Path, process};
+
+use chrono::{NaiveDate, TimeDelta};
+
+use crate::{
+    check::{check_file, get_new_problems},
+    get_user_input,
+    ids::{as_id_search, get_id_text, get_line_ids},
+    parser::{
+        DateSpec, LineSpec, is_day, is_time_word, parse_line, parse_relative_date, split_line,
+        tokenize,
+    },
+    resolve_relative_date,
+    search::Matcher,
+    utils::{
+        ask_option, confirm, get_rrem_file_options, read_lines_from_file, replace_line_in_file,
+    },
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Change {
+    Delete,
+    Move,
+    Edit,
+}
+
+impl Change {
+    fn name(&self) -> &'static str {
+        match self {
+            Change::Delete => "delete",
+            Change::Move => "move",
+            Change::Edit => "edit",
+        }
+    }
+}
+
+// A line of a .rem-file, and where it is
+#[derive(Debug, PartialEq)]
+pub(crate) struct Found {
+    pub(crate) file: String,   // e.g. work.rem
+    pub(crate) line_no: usize, // 1-based
+    pub(crate) line: String,
+    pub(crate) id: Option<String>, // None for comments and blank lines
+}
+
+impl fmt::Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}{}",
+            self.file,
+            self.line_no,
+            self.line,
+            get_id_text(&self.id)
+        )
+    }
+}
+
+// Flags that are not part of the search term or of the new date
+const FLAGS: [&str; 5] = [
+    "--yes",
+    "--regex",
+    "--fuzzy",
+    "--case-sensitive",
+    "--whole-line",
+];
+
+/// Run `rremind delete|move|edit ...` (`args` as on the command line);
+/// full dates are written in `date_format`
+pub(crate) fn change_appointment(
+    change: Change,
+    args: &[String],
+    dir: &str,
+    date_format: &str,
+) -> Result<(), String> {
+    let yes = args.iter().any(|arg| arg == "--yes");
+    let words = get_words(args);
+    let usage = match change {
+        Change::Move => "rremind move <search> <new date/time>".to_string(),
+        _ => format!("rremind {} <search>", change.name()),
+    };
+    let Some(search) = words.first() else {
+        return Err(format!("What should I {}? Usage: `{usage}`", change.name()));
+    };
+    let target = words[1..].join(" ");
+    if change == Change::Move && target.is_empty() {
+        return Err(format!("Where to? Usage: `{usage}`"));
+    }
+
+    let found = find_one(search, args, dir, change.name(), yes)?;
+    let new_line = match change {
+        Change::Delete => None,
+        Change::Move => {
+            let spec = parse_line(&found.line).map_err(|e| {
+                format!(
+                    "Cannot read {}: {e}; try `rremind edit {}:{}`",
+                    found.file, found.file, found.line_no
+                )
+            })?;
+            let today = chrono::offset::Local::now().date_naive();
+            let moved = move_spec(&spec, &resolve_target(&target, &spec, today))?;
+            Some(moved.to_line_with_ignored(date_format).map_err(|e| {
+                format!(
+                    "Cannot move {}:{}: {e}; try `rremind edit {}:{}`",
+                    found.file, found.line_no, found.file, found.line_no
+                )
+            })?)
+        }
+        Change::Edit => {
+            println!("{found}");
+            let input = get_user_input("How should the line read?", &found.line);
+            let input =
+                resolve_relative_date(input.trim(), chrono::offset::Local::now().date_naive());
+            let spec = parse_line(&input)
+                .map_err(|e| format!("Cannot read `{input}`: {e}\n  try: {}", e.suggestion))?;
+            if spec.description.is_none() {
+                return Err(format!(
+                    "`{input}` has no description. Separate it from the date with a comma, e.g. `Mon 13:00, lunch`"
+                ));
+            }
+            Some(spec.to_line_with_ignored(date_format).map_err(|e| {
+                format!(
+                    "Cannot write `{input}`: {e}; put them into the description, after the comma"
+                )
+            })?)
+        }
+    };
+    if new_line.as_deref() == Some(found.line.as_str()) {
+        println!("Nothing to change: {found}");
+        return Ok(());
+    }
+
+    let question = match &new_line {
+        None => format!("Delete `{}` from {}? (y/n)", found.line, found.file),
+        Some(line) => format!(
+            "Change `{}` to `{line}` in {}? (y/n)",
+            found.line, found.file
+        ),
+    };
+    if !yes && !confirm(&question, change != Change::Delete) {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+    let path = Path::new(dir).join(&found.file);
+    replace_line_in_file(
+        &path.to_string_lossy(),
+        found.line_no,
+        &found.line,
+        new_line.as_deref(),
+    )?;
+    match new_line {
+        None => println!("Deleted from {}: {}", found.file, found.line),
+        Some(line) => println!("{}:{}: {line}", found.file, found.line_no),
+    }
+    Ok(())
+}
+
+/// Run `rremind open <search|file:line>`: open the line in $VISUAL (or
+/// $EDITOR, or vi) at its line number (`+N`), then check the file again.
+/// Returns the number of problems that are new after editing.
+pub(crate) fn open_in_editor(args: &[String], dir: &str) -> Result<usize, String> {
+    let Some(search) = get_words(args).first().copied() else {
+        return Err("What should I open? Usage: `rremind open <search|file:line>`".to_string());
+    };
+    let found = find_one(search, args, dir, "open", false)?;
+
+    let path = Path::new(dir).join(&found.file);
+    let file_name = path.to_string_lossy().to_string();
+    let before = check_file(&file_name, &read_lines_from_file(&file_name).join("\n"));
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or("vi".to_string());
+    // $EDITOR may come with arguments, e.g. "code --wait"
+    let mut editor_words = editor.split_whitespace();
+    let program = editor_words.next().unwrap_or("vi");
+    let status = process::Command::new(program)
+        .args(editor_words)
+        .arg(format!("+{}", found.line_no))
+        .arg(&path)
+        .status()
+        .map_err(|e| format!("Cannot start `{editor}`: {e}"))?;
+    if !status.success() {
+        return Err(format!(
+            "`{editor}` did not finish successfully ({status})."
+        ));
+    }
+
+    let after = check_file(&file_name, &read_lines_from_file(&file_name).join("\n"));
+    let new = get_new_problems(&before, &after);
+    for problem in &new {
+        println!("{problem}");
+    }
+    match (new.len(), after.len() - new.len()) {
+        (0, 0) => println!("{} reads fine.", found.file),
+        (0, old) => println!(
+            "No new problems in {} ({old} as before; see `rremind check`).",
+            found.file
+        ),
+        (n, _) => println!("{n} new problem(s) in {}.", found.file),
+    }
+    Ok(new.len())
+}
+
+/// The words after `rremind <command>`, without flags
+pub(crate) fn get_words(args: &[String]) -> Vec<&str> {
+    args.iter()
+        .skip(2)
+        .map(|arg| arg.as_str())
+        .filter(|arg| !FLAGS.contains(arg))
+        .collect()
+}
+
+/// The one line `rremind <command> <search>` is about (see `find_lines`);
+/// if several lines match, the user picks one (unless `yes`)
+pub(crate) fn find_one(
+    search: &str,
+    args: &[String],
+    dir: &str,
+    command: &str,
+    yes: bool,
+) -> Result<Found, String> {
+    let matcher = Matcher::from_args(search, args)?;
+    select(find_lines(dir, search, &matcher), search, command, yes)
+}
+
+/// The lines of the .rem-files in `dir` that `search` stands for: the
+/// line at a place (`work.rem:3`, `work:3`, `work.rem:3:5`), the line
+/// with an id (`id:3f2a9c01`, see ids/mod.rs), or the appointments that
+/// `matcher` finds
+pub(crate) fn find_lines(dir: &str, search: &str, matcher: &Matcher) -> Vec<Found> {
+    let files = get_rrem_file_options();
+    let place = parse_place(search).filter(|(file, _)| files.contains(file));
+    let id = as_id_search(search);
+
+    let mut found: Vec<Found> = vec![];
+    for file in &files {
+        let lines = read_lines_from_file(&Path::new(dir).join(file).to_string_lossy());
+        let ids = get_line_ids(file, lines.iter().map(|line| line.as_str()));
+        for (i, (line, line_id)) in lines.iter().zip(ids).enumerate() {
+            let wanted = match (&place, id) {
+                (Some((place_file, line_no)), _) => place_file == file && *line_no == i + 1,
+                (None, Some(id)) => line_id
+                    .as_deref()
+                    .is_some_and(|line_id| line_id.eq_ignore_ascii_case(id)),
+                (None, None) => {
+                    line_id.is_some() && matcher.matches(line, split_line(line).1.unwrap_or(""))
+                }
+            };
+            if wanted {
+                found.push(Found {
+                    file: file.clone(),
+                    line_no: i + 1,
+                    line: line.to_string(),
+                    id: line_id,
+                });
+            }
+        }
+    }
+    found
+}
+
+// The place of a line: "work.rem:3", "work:3", or what `check` reports,
+// "/home/me/rem/work.rem:3:5:" => ("work.rem", 3)
+fn parse_place(search: &str) -> Option<(String, usize)> {
+    let mut parts = search.trim_end_matches(':').split(':');
+    let name = Path::new(parts.next()?)
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+    let line_no = parts.next()?.parse::<usize>().ok()?;
+    let column = parts.next();
+    if column.is_some_and(|column| column.parse::<usize>().is_err()) || parts.next().is_some() {
+        return None;
+    }
+    match name.ends_with(".rem") {
+        true => Some((name, line_no)),
+        false => Some((format!("{name}.rem"), line_no)),
+    }
+}
+
+// One of the lines found: the only one, or the one the user picks
+fn select(mut found: Vec<Found>, search: &str, command: &str, yes: bool) -> Result<Found, String> {
+    // An id that several lines have (set by hand, `ID:gym`) names none of them
+    if as_id_search(search).is_some() && found.len() > 1 {
+        let lines: Vec<String> = found.iter().map(|f| format!("  {f}")).collect();
+        return Err(format!(
+            "{} appointments have the id `{search}`:\n{}\nGive each its own ID: (see `rremind check`), or name one by its place, e.g. `rremind {command} {}:{}`.",
+            found.len(),
+            lines.join("\n"),
+            found[0].file,
+            found[0].line_no
+        ));
+    }
+    match found.len() {
+        0 => Err(format!("No appointment matches `{search}`.")),
+        1 => Ok(found.remove(0)),
+        n if yes => {
+            let lines: Vec<String> = found.iter().map(|f| format!("  {f}")).collect();
+            Err(format!(
+                "{n} appointments match `{search}`:\n{}\nName one by its place or id, e.g. `rremind {command} {}:{}`{}.",
+                lines.join("\n"),
+                found[0].file,
+                found[0].line_no,
+                match &found[0].id {
+                    Some(id) => format!(" or `rremind {command} id:{id}`"),
+                    None => "".to_string(),
+                }
+            ))
+        }
+        _ => {
+            let options: Vec<String> = found.iter().map(|f| f.to_string()).collect();
+            let choice = ask_option(&format!("Which one should I {command}?"), options.clone())
+                .ok_or("Sorry, I didn't understand your choice; nothing was changed.")?;
+            let index = options
+                .iter()
+                .position(|option| *option == choice)
+                .unwrap_or(0);
+            Ok(found.remove(index))
+        }
+    }
+}
+
+// "next fri 10:00" => "2025-05-23 10:00"; a weekday alone, too, unless
+// the appointment is weekly ("move standup tue" keeps it weekly)
+fn resolve_target(target: &str, spec: &LineSpec, today: NaiveDate) -> String {
+    let words: Vec<&str> = target.split_whitespace().collect();
+    match parse_relative_date(&words, today) {
+        Some((_, 1)) if matches!(spec.date, DateSpec::Weekly(_)) && is_day(words[0]) => {
+            target.to_string()
+        }
+        Some((datum, used)) => {
+            let mut line = vec![datum.format("%Y-%m-%d").to_string()];
+            line.extend(words[used..].iter().map(|word| word.to_string()));
+            line.join(" ")
+        }
+        None => target.to_string(),
+    }
+}
+
+/// `spec` moved to the date and/or time of `target` ("2025-05-23 10:00",
+/// "tue", "14:30"); what `target` does not say stays as it was. A new
+/// start time keeps the length of the appointment.
+pub(crate) fn move_spec(spec: &LineSpec, target: &str) -> Result<LineSpec, String> {
+    let time_only = tokenize(target)
+        .first()
+        .is_some_and(|token| is_time_word(&token.text));
+    let target_line = match time_only {
+        true => format!("2000-01-01 {target}"),
+        false => target.to_string(),
+    };
+    let to = parse_line(&target_line)
+        .map_err(|e| format!("Cannot read `{target}`: {}: {}", e.part, e.message))?;
+
+    let mut moved = spec.clone();
+    if !time_only {
+        moved.date = to.date;
+    }
+    if let Some(start) = to.start {
+        moved.start = Some(start);
+        match (to.stop, to.duration, spec.start, spec.stop) {
+            (None, None, Some(old_start), Some(old_stop)) => {
+                let length: TimeDelta = old_stop - old_start;
+                moved.stop = Some(start + length);
+            }
+            (None, None, _, _) => {}
+            (stop, duration, _, _) => {
+                moved.stop = stop;
+                moved.duration = duration;
+            }
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod test_edit {
+    use chrono::NaiveDate;
+
+    use super::{move_spec, parse_place, resolve_target};
+    use crate::parser::parse_line;
+
+    #[test]
+    fn edit_move() {
+        let moved = |line: &str, target: &str| {
+            move_spec(&parse_line(line).unwrap(), target)
+                .unwrap()
+                .to_line_with_ignored("%d.%m.%Y")
+                .unwrap()
+        };
+        assert_eq!(
+            moved("2025-05-17 10:00-11:30, dentist", "2025-05-23 14:00"),
+            "23.05.2025 14:00-15:30, dentist"
+        );
+        assert_eq!(
+            moved("2025-05-17 10:00 DURATION 1 WARN 2, dentist", "2025-05-23"),
+            "23.05.2025 10:00 DURATION 1 WARN 2, dentist"
+        );
+        assert_eq!(
+            moved("mon-fri 9:15 SKIP-HOLIDAY, standup", "9:30"),
+            "Mon-Fri 09:30 SKIP-HOLIDAY, standup"
+        );
+        assert_eq!(
+            moved("mon 18:00-19:00, choir", "tue 17:00-19:00"),
+            "Tue 17:00-19:00, choir"
+        );
+        // the room number stays
+        assert_eq!(
+            moved("2026-10-20 10:00 Raum 122, meeting", "2026-10-21"),
+            "21.10.2026 10:00 Raum 122, meeting"
+        );
+        assert!(move_spec(&parse_line("mon, x").unwrap(), "25:00").is_err());
+    }
+
+    #[test]
+    fn edit_move_target() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 17).unwrap(); // a Saturday
+        let dentist = parse_line("2025-05-17 10:00, dentist").unwrap();
+        let standup = parse_line("mon 9:15, standup").unwrap();
+        assert_eq!(
+            resolve_target("next fri 14:00", &dentist, today),
+            "2025-05-23 14:00"
+        );
+        assert_eq!(resolve_target("fri", &dentist, today), "2025-05-23");
+        assert_eq!(resolve_target("tue", &standup, today), "tue");
+        assert_eq!(resolve_target("14:00", &dentist, today), "14:00");
+    }
+
+    #[test]
+    fn edit_place() {
+        let place = |file: &str, line_no: usize| Some((file.to_string(), line_no));
+        assert_eq!(parse_place("work.rem:3"), place("work.rem", 3));
+        assert_eq!(parse_place("work:3"), place("work.rem", 3));
+        assert_eq!(
+            parse_place("/home/me/rem/work.rem:4:5:"),
+            place("work.rem", 4)
+        );
+        assert_eq!(parse_place("dentist"), None);
+        assert_eq!(parse_place("work:three"), None);
+    }
+}
@@ -0,0 +1,247 @@
+// How dates and times are shown (and how `add` writes dates), set in rr.rc:
+//
+// rremind_date_format=%a, %d.%m.%Y   (day headers; default %Y-%m-%d (%a))
+// rremind_time_format=12h            (or 24h, the default)
+// rremind_time_separator=:           (between hour and minute; default .)
+// rremind_time_suffix=               (after the time; default Uhr)
+// rremind_language=de                (weekday and month names; default en)
+// rremind_add_date_format=%Y-%m-%d   (dates written by `add`; default %d.%m.%Y)
+
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate, NaiveTime};
+
+use crate::parser::{as_date, between};
+
+const DATE_FORMAT: &str = "%Y-%m-%d (%a)";
+const ADD_DATE_FORMAT: &str = "%d.%m.%Y";
+
+const DE_DAYS: &[&str] = &[
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+const DE_MONTHS: &[&str] = &[
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Language {
+    En,
+    De,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DisplayFormat {
+    date_format: String,
+    twelve_hour: bool,
+    time_separator: String,
+    time_suffix: String,
+    language: Language,
+    pub(crate) add_date_format: String,
+}
+
+impl Default for DisplayFormat {
+    fn default() -> Self {
+        DisplayFormat {
+            date_format: DATE_FORMAT.to_string(),
+            twelve_hour: false,
+            time_separator: ".".to_string(),
+            time_suffix: "Uhr".to_string(),
+            language: Language::En,
+            add_date_format: ADD_DATE_FORMAT.to_string(),
+        }
+    }
+}
+
+impl DisplayFormat {
+    /// Settings from the text of rr.rc; missing keys keep their default
+    pub(crate) fn from_config(cfile: &str) -> DisplayFormat {
+        let default = DisplayFormat::default();
+        // An empty value is a setting, too (no suffix after the time)
+        let value = |key: &str, default: String| match cfile.contains(&format!("{key}=")) {
+            true => between(cfile, &format!("{key}="), "\n").to_string(),
+            false => default,
+        };
+        let non_empty = |key: &str, default: String| match between(cfile, &format!("{key}="), "\n")
+        {
+            "" => default,
+            v => v.to_string(),
+        };
+        DisplayFormat {
+            date_format: match non_empty("rremind_date_format", default.date_format) {
+                f if is_date_format(&f) => f,
+                f => {
+                    eprintln!(
+                        "rremind_date_format={f} is not a date format rremind knows; using {DATE_FORMAT} instead."
+                    );
+                    DATE_FORMAT.to_string()
+                }
+            },
+            twelve_hour: between(cfile, "rremind_time_format=", "\n").eq_ignore_ascii_case("12h"),
+            time_separator: value("rremind_time_separator", default.time_separator),
+            time_suffix: value("rremind_time_suffix", default.time_suffix),
+            language: match between(cfile, "rremind_language=", "\n")
+                .to_lowercase()
+                .as_str()
+            {
+                "de" => Language::De,
+                _ => Language::En,
+            },
+            add_date_format: match non_empty("rremind_add_date_format", default.add_date_format) {
+                f if is_readable_date_format(&f) => f,
+                f => {
+                    eprintln!(
+                        "rremind_add_date_format={f} writes dates rremind cannot read; using {ADD_DATE_FORMAT} instead."
+                    );
+                    ADD_DATE_FORMAT.to_string()
+                }
+            },
+        }
+    }
+
+    /// Lines for rr.rc
+    pub(crate) fn to_config_text(&self) -> String {
+        format!(
+            "rremind_date_format={}\nrremind_time_format={}\nrremind_time_separator={}\nrremind_time_suffix={}\nrremind_language={}\nrremind_add_date_format={}\n",
+            self.date_format,
+            match self.twelve_hour {
+                true => "12h",
+                false => "24h",
+            },
+            self.time_separator,
+            self.time_suffix,
+            match self.language {
+                Language::En => "en",
+                Language::De => "de",
+            },
+            self.add_date_format
+        )
+    }
+
+    /// Date as in the day headers: "2025-05-17 (Sat)"
+    pub(crate) fn format_date(&self, datum: NaiveDate) -> String {
+        datum
+            .format(&self.localize(&self.date_format, datum))
+            .to_string()
+    }
+
+    /// "10.00", "10:00", or "10:00 am"
+    pub(crate) fn format_time(&self, time: NaiveTime) -> String {
+        let pattern = match self.twelve_hour {
+            true => format!("%-I{}%M %P", self.time_separator),
+            false => format!("%H{}%M", self.time_separator),
+        };
+        time.format(&pattern).to_string()
+    }
+
+    /// " (10.00 Uhr)", " (10.00-11.30 Uhr)", or "" without a start time
+    pub(crate) fn format_period(
+        &self,
+        start: Option<NaiveTime>,
+        stop: Option<NaiveTime>,
+    ) -> String {
+        let Some(start) = start else {
+            return "".to_string();
+        };
+        let suffix = match self.time_suffix.is_empty() {
+            true => "".to_string(),
+            false => format!(" {}", self.time_suffix),
+        };
+        match stop {
+            Some(stop) => format!(
+                " ({}-{}{suffix})",
+                self.format_time(start),
+                self.format_time(stop)
+            ),
+            None => format!(" ({}{suffix})", self.format_time(start)),
+        }
+    }
+
+    // chrono only knows English names: put the German ones into the format
+    fn localize(&self, format: &str, datum: NaiveDate) -> String {
+        match self.language {
+            Language::En => format.to_string(),
+            Language::De => {
+                let day = DE_DAYS[datum.weekday().num_days_from_monday() as usize];
+                let month = DE_MONTHS[datum.month0() as usize];
+                format
+                    .replace("%A", day)
+                    .replace("%a", &day[0..2])
+                    .replace("%B", month)
+                    .replace("%b", &month.chars().take(3).collect::<String>())
+            }
+        }
+    }
+}
+
+// Can a date be written in `date_format`? Not with an unknown %-code (%Q)
+fn is_date_format(date_format: &str) -> bool {
+    let sample = NaiveDate::from_ymd_opt(2025, 5, 17).unwrap();
+    write!(String::new(), "{}", sample.format(date_format)).is_ok()
+}
+
+// Dates that `add`, `fmt` and `move` write in `date_format` must read
+// back as the same date (%m/%d/%Y does not, nor does an unknown %-code)
+fn is_readable_date_format(date_format: &str) -> bool {
+    let sample = NaiveDate::from_ymd_opt(2025, 5, 17).unwrap();
+    is_date_format(date_format) && as_date(&sample.format(date_format).to_string()) == Some(sample)
+}
+
+#[cfg(test)]
+mod test_format {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::DisplayFormat;
+
+    #[test]
+    fn format_defaults() {
+        let f = DisplayFormat::from_config("rremind_files=/tmp\n");
+        assert_eq!(f, DisplayFormat::default());
+        let datum = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        assert_eq!(f.format_date(datum), "2025-03-01 (Sat)");
+        let ten = NaiveTime::from_hms_opt(10, 0, 0);
+        let half_past_one = NaiveTime::from_hms_opt(13, 30, 0);
+        assert_eq!(f.format_period(ten, None), " (10.00 Uhr)");
+        assert_eq!(f.format_period(ten, half_past_one), " (10.00-13.30 Uhr)");
+        assert_eq!(f.format_period(None, None), "");
+    }
+
+    #[test]
+    fn format_configured() {
+        let f = DisplayFormat::from_config(
+            "rremind_date_format=%a, %d. %b %Y\nrremind_time_format=12h\nrremind_time_separator=:\nrremind_time_suffix=\nrremind_language=de\n",
+        );
+        let datum = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        assert_eq!(f.format_date(datum), "Sa, 01. Mär 2025");
+        let period = f.format_period(
+            NaiveTime::from_hms_opt(9, 5, 0),
+            NaiveTime::from_hms_opt(13, 30, 0),
+        );
+        assert_eq!(period, " (9:05 am-1:30 pm)");
+        assert_eq!(DisplayFormat::from_config(&f.to_config_text()), f);
+        // `add` must write dates that rremind reads
+        let add = |f: &str| DisplayFormat::from_config(&format!("rremind_add_date_format={f}\n"));
+        assert_eq!(add("%Y-%m-%d").add_date_format, "%Y-%m-%d");
+        assert_eq!(add("%m/%d/%Y").add_date_format, "%d.%m.%Y");
+        assert_eq!(add("%Q").add_date_format, "%d.%m.%Y");
+        // ... and the day headers must be possible to write
+        let f = DisplayFormat::from_config("rremind_date_format=%Q\n");
+        assert_eq!(f, DisplayFormat::default());
+    }
+}
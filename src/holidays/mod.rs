@@ -0,0 +1,299 @@
+// Public holidays, calculated from rules that come with rremind
+// (no network needed). The regions are chosen in rr.rc, e.g.
+//
+// rremind_holidays=DE-BW
+// rremind_holidays=DE-BY,AT
+//
+// A state (DE-BW) has the holidays of its country (DE) plus its own.
+// Substitute days (a holiday on a Sunday moved to Monday) are not
+// calculated, neither are school holidays: these change every year,
+// so they had better go into a .rem-file.
+
+use std::sync::OnceLock;
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+use crate::parser::easter_sunday;
+
+enum Rule {
+    Fixed(u32, u32),                  // month, day
+    Easter(i64),                      // days relative to Easter Sunday
+    NthWeekday(u32, Weekday, i32),    // month, weekday, n-th (-1 is the last)
+    WeekdayBefore(u32, u32, Weekday), // last weekday before month, day
+}
+
+struct Holiday {
+    name: &'static str,
+    rule: Rule,
+}
+
+const fn fixed(name: &'static str, month: u32, day: u32) -> Holiday {
+    Holiday {
+        name,
+        rule: Rule::Fixed(month, day),
+    }
+}
+
+const fn easter(name: &'static str, offset: i64) -> Holiday {
+    Holiday {
+        name,
+        rule: Rule::Easter(offset),
+    }
+}
+
+const fn nth(name: &'static str, month: u32, weekday: Weekday, n: i32) -> Holiday {
+    Holiday {
+        name,
+        rule: Rule::NthWeekday(month, weekday, n),
+    }
+}
+
+const DE: &[Holiday] = &[
+    fixed("Neujahr", 1, 1),
+    easter("Karfreitag", -2),
+    easter("Ostermontag", 1),
+    fixed("Tag der Arbeit", 5, 1),
+    easter("Christi Himmelfahrt", 39),
+    easter("Pfingstmontag", 50),
+    fixed("Tag der Deutschen Einheit", 10, 3),
+    fixed("1. Weihnachtstag", 12, 25),
+    fixed("2. Weihnachtstag", 12, 26),
+];
+
+const HEILIGE_DREI_KOENIGE: Holiday = fixed("Heilige Drei Könige", 1, 6);
+const FRAUENTAG: Holiday = fixed("Internationaler Frauentag", 3, 8);
+const FRONLEICHNAM: Holiday = easter("Fronleichnam", 60);
+const MARIAE_HIMMELFAHRT: Holiday = fixed("Mariä Himmelfahrt", 8, 15);
+const REFORMATIONSTAG: Holiday = fixed("Reformationstag", 10, 31);
+const ALLERHEILIGEN: Holiday = fixed("Allerheiligen", 11, 1);
+
+const DE_BW: &[Holiday] = &[HEILIGE_DREI_KOENIGE, FRONLEICHNAM, ALLERHEILIGEN];
+const DE_BY: &[Holiday] = &[
+    HEILIGE_DREI_KOENIGE,
+    FRONLEICHNAM,
+    MARIAE_HIMMELFAHRT,
+    ALLERHEILIGEN,
+];
+const DE_BE: &[Holiday] = &[FRAUENTAG];
+const DE_BB: &[Holiday] = &[
+    easter("Ostersonntag", 0),
+    easter("Pfingstsonntag", 49),
+    REFORMATIONSTAG,
+];
+const DE_HE: &[Holiday] = &[FRONLEICHNAM];
+const DE_MV: &[Holiday] = &[FRAUENTAG, REFORMATIONSTAG];
+const DE_NORTH: &[Holiday] = &[REFORMATIONSTAG]; // HB, HH, NI, SH
+const DE_NW: &[Holiday] = &[FRONLEICHNAM, ALLERHEILIGEN]; // also RP
+const DE_SL: &[Holiday] = &[FRONLEICHNAM, MARIAE_HIMMELFAHRT, ALLERHEILIGEN];
+const DE_SN: &[Holiday] = &[
+    REFORMATIONSTAG,
+    Holiday {
+        name: "Buß- und Bettag",
+        rule: Rule::WeekdayBefore(11, 23, Weekday::Wed),
+    },
+];
+const DE_ST: &[Holiday] = &[HEILIGE_DREI_KOENIGE, REFORMATIONSTAG];
+const DE_TH: &[Holiday] = &[fixed("Weltkindertag", 9, 20), REFORMATIONSTAG];
+
+const AT: &[Holiday] = &[
+    fixed("Neujahr", 1, 1),
+    HEILIGE_DREI_KOENIGE,
+    easter("Ostermontag", 1),
+    fixed("Staatsfeiertag", 5, 1),
+    easter("Christi Himmelfahrt", 39),
+    easter("Pfingstmontag", 50),
+    FRONLEICHNAM,
+    MARIAE_HIMMELFAHRT,
+    fixed("Nationalfeiertag", 10, 26),
+    ALLERHEILIGEN,
+    fixed("Mariä Empfängnis", 12, 8),
+    fixed("Christtag", 12, 25),
+    fixed("Stefanitag", 12, 26),
+];
+
+const CH: &[Holiday] = &[
+    fixed("Neujahrstag", 1, 1),
+    easter("Auffahrt", 39),
+    fixed("Bundesfeier", 8, 1),
+    fixed("Weihnachtstag", 12, 25),
+];
+
+const FR: &[Holiday] = &[
+    fixed("Jour de l'an", 1, 1),
+    easter("Lundi de Pâques", 1),
+    fixed("Fête du Travail", 5, 1),
+    fixed("Victoire 1945", 5, 8),
+    easter("Ascension", 39),
+    easter("Lundi de Pentecôte", 50),
+    fixed("Fête nationale", 7, 14),
+    fixed("Assomption", 8, 15),
+    fixed("Toussaint", 11, 1),
+    fixed("Armistice 1918", 11, 11),
+    fixed("Noël", 12, 25),
+];
+
+const GB: &[Holiday] = &[
+    fixed("New Year's Day", 1, 1),
+    easter("Good Friday", -2),
+    easter("Easter Monday", 1),
+    nth("Early May bank holiday", 5, Weekday::Mon, 1),
+    nth("Spring bank holiday", 5, Weekday::Mon, -1),
+    nth("Summer bank holiday", 8, Weekday::Mon, -1),
+    fixed("Christmas Day", 12, 25),
+    fixed("Boxing Day", 12, 26),
+];
+
+const US: &[Holiday] = &[
+    fixed("New Year's Day", 1, 1),
+    nth("Martin Luther King Jr. Day", 1, Weekday::Mon, 3),
+    nth("Presidents' Day", 2, Weekday::Mon, 3),
+    nth("Memorial Day", 5, Weekday::Mon, -1),
+    fixed("Juneteenth", 6, 19),
+    fixed("Independence Day", 7, 4),
+    nth("Labor Day", 9, Weekday::Mon, 1),
+    nth("Columbus Day", 10, Weekday::Mon, 2),
+    fixed("Veterans Day", 11, 11),
+    nth("Thanksgiving", 11, Weekday::Thu, 4),
+    fixed("Christmas Day", 12, 25),
+];
+
+const REGIONS: &[(&str, &[&[Holiday]])] = &[
+    ("DE", &[DE]),
+    ("DE-BW", &[DE, DE_BW]),
+    ("DE-BY", &[DE, DE_BY]),
+    ("DE-BE", &[DE, DE_BE]),
+    ("DE-BB", &[DE, DE_BB]),
+    ("DE-HB", &[DE, DE_NORTH]),
+    ("DE-HH", &[DE, DE_NORTH]),
+    ("DE-HE", &[DE, DE_HE]),
+    ("DE-MV", &[DE, DE_MV]),
+    ("DE-NI", &[DE, DE_NORTH]),
+    ("DE-NW", &[DE, DE_NW]),
+    ("DE-RP", &[DE, DE_NW]),
+    ("DE-SL", &[DE, DE_SL]),
+    ("DE-SN", &[DE, DE_SN]),
+    ("DE-ST", &[DE, DE_ST]),
+    ("DE-SH", &[DE, DE_NORTH]),
+    ("DE-TH", &[DE, DE_TH]),
+    ("AT", &[AT]),
+    ("CH", &[CH]),
+    ("FR", &[FR]),
+    ("GB", &[GB]),
+    ("US", &[US]),
+];
+
+// The regions configured in rr.rc (set once, in main)
+static CONFIGURED_REGIONS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Remember the regions configured in rr.rc ("DE-BW,AT"); unknown
+/// regions are reported and ignored.
+pub(crate) fn set_regions(regions: &str) {
+    let mut known: Vec<String> = vec![];
+    for region in regions.split(',').map(|r| r.trim().to_uppercase()) {
+        if region.is_empty() {
+            continue;
+        }
+        match REGIONS.iter().any(|(code, _)| *code == region) {
+            true => known.push(region),
+            false => eprintln!(
+                "Sorry, there are no holidays for region `{region}` (known regions: {}).",
+                REGIONS
+                    .iter()
+                    .map(|(code, _)| *code)
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+        }
+    }
+    let _ = CONFIGURED_REGIONS.set(known);
+}
+
+/// Names of the holidays on `datum` in the configured regions
+pub(crate) fn get_holidays(datum: NaiveDate) -> Vec<&'static str> {
+    match CONFIGURED_REGIONS.get() {
+        Some(regions) => get_holidays_in(regions, datum),
+        None => vec![],
+    }
+}
+
+/// Is `datum` a holiday in one of the configured regions?
+pub(crate) fn is_holiday(datum: NaiveDate) -> bool {
+    !get_holidays(datum).is_empty()
+}
+
+fn get_holidays_in(regions: &[String], datum: NaiveDate) -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = vec![];
+    for (_, lists) in REGIONS
+        .iter()
+        .filter(|(code, _)| regions.iter().any(|r| r == code))
+    {
+        for holiday in lists.iter().flat_map(|list| list.iter()) {
+            if get_date(&holiday.rule, datum.year()) == Some(datum)
+                && !names.contains(&holiday.name)
+            {
+                names.push(holiday.name);
+            }
+        }
+    }
+    names
+}
+
+fn get_date(rule: &Rule, year: i32) -> Option<NaiveDate> {
+    match rule {
+        Rule::Fixed(month, day) => NaiveDate::from_ymd_opt(year, *month, *day),
+        Rule::Easter(offset) => {
+            easter_sunday(year)?.checked_add_signed(chrono::TimeDelta::try_days(*offset)?)
+        }
+        Rule::NthWeekday(month, weekday, n) => match *n > 0 {
+            true => NaiveDate::from_weekday_of_month_opt(year, *month, *weekday, *n as u8),
+            false => {
+                // Last weekday of the month: count back from the end
+                let last = NaiveDate::from_ymd_opt(year, *month + 1, 1)
+                    .or(NaiveDate::from_ymd_opt(year + 1, 1, 1))?
+                    .pred_opt()?;
+                let back = (7 + last.weekday().num_days_from_monday()
+                    - weekday.num_days_from_monday())
+                    % 7;
+                last.checked_sub_days(Days::new(back as u64))
+            }
+        },
+        Rule::WeekdayBefore(month, day, weekday) => {
+            let mut datum = NaiveDate::from_ymd_opt(year, *month, *day)?.pred_opt()?;
+            while datum.weekday() != *weekday {
+                datum = datum.pred_opt()?;
+            }
+            Some(datum)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_holidays {
+    use chrono::NaiveDate;
+
+    use crate::holidays::get_holidays_in;
+
+    fn on(region: &str, y: i32, m: u32, d: u32) -> Vec<&'static str> {
+        get_holidays_in(
+            &[region.to_string()],
+            NaiveDate::from_ymd_opt(y, m, d).unwrap(),
+        )
+    }
+
+    #[test]
+    fn holidays_germany() {
+        assert_eq!(on("DE-BW", 2025, 6, 19), vec!["Fronleichnam"]);
+        assert!(on("DE-BE", 2025, 6, 19).is_empty());
+        assert_eq!(on("DE", 2025, 4, 18), vec!["Karfreitag"]);
+        assert_eq!(on("DE-SN", 2025, 11, 19), vec!["Buß- und Bettag"]);
+        assert_eq!(on("DE-SN", 2023, 11, 22), vec!["Buß- und Bettag"]);
+    }
+
+    #[test]
+    fn holidays_weekday_rules() {
+        assert_eq!(on("US", 2025, 11, 27), vec!["Thanksgiving"]);
+        assert_eq!(on("US", 2025, 5, 26), vec!["Memorial Day"]);
+        assert_eq!(on("GB", 2025, 8, 25), vec!["Summer bank holiday"]);
+        assert!(on("US", 2025, 11, 20).is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+// Ids of appointments, to name one line even if another reads the same.
+//
+// A line with an `ID:` item (`2025-05-17 10:00 ID:dentist, dentist`) has
+// this id. Any other line gets one from the name of its .rem-file and
+// its text: eight hex digits of a hash (FNV-1a, so it is the same in
+// every version of rremind). Identical lines in one file are counted,
+// so their ids differ (if the first of them goes, the second takes over
+// its id). The id stays as long as the line does; changing the line
+// (`move`, `edit`, `fmt`) changes it, unless it has an `ID:`. Two lines
+// with the same `ID:` are reported by `check`, and their id names neither.
+//
+// On the command line, an id is written `id:3f2a9c01` (or `id:dentist`).
+
+use crate::parser::parse_line_leniently;
+
+const ID_PREFIX: &str = "id:";
+
+/// The ids of the lines of the .rem-file `file` (its name, e.g.
+/// work.rem); None for comments and blank lines
+pub(crate) fn get_line_ids<'a>(
+    file: &str,
+    lines: impl Iterator<Item = &'a str>,
+) -> Vec<Option<String>> {
+    let mut seen: Vec<&str> = vec![];
+    let mut ids: Vec<Option<String>> = vec![];
+    for line in lines {
+        if line.starts_with('#') || line.trim().is_empty() {
+            ids.push(None);
+            continue;
+        }
+        let copies = seen.iter().filter(|other| **other == line).count();
+        seen.push(line);
+        let id = match parse_line_leniently(line).ok().and_then(|spec| spec.id) {
+            Some(id) => id,
+            None => format!("{:08x}", hash(&format!("{file}\n{line}\n{copies}")) as u32),
+        };
+        ids.push(Some(id));
+    }
+    ids
+}
+
+/// "id:3f2a9c01" => Some("3f2a9c01")
+pub(crate) fn as_id_search(search: &str) -> Option<&str> {
+    match search.get(..ID_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(ID_PREFIX) => Some(&search[ID_PREFIX.len()..]),
+        _ => None,
+    }
+}
+
+/// How an id is shown in listings: " [id:3f2a9c01]"
+pub(crate) fn get_id_text(id: &Option<String>) -> String {
+    match id {
+        Some(id) => format!(" [{ID_PREFIX}{id}]"),
+        None => "".to_string(),
+    }
+}
+
+// 64-bit FNV-1a
+fn hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod test_ids {
+    use super::{as_id_search, get_line_ids};
+
+    #[test]
+    fn ids_of_lines() {
+        let contents = "# color=red\nMon 10:00, x\n\nMon 10:00, x\n2025-05-17 ID:dentist, dentist";
+        let ids = get_line_ids("work.rem", contents.lines());
+        assert_eq!(ids[0], None);
+        assert_eq!(ids[2], None);
+        assert_eq!(ids[4].as_deref(), Some("dentist"));
+        // identical lines differ, and so do files
+        assert_ne!(ids[1], ids[3]);
+        assert_ne!(ids[1], get_line_ids("home.rem", contents.lines())[1]);
+        // the same line has the same id, wherever it is in the file
+        let moved = get_line_ids("work.rem", "Tue, y\nMon 10:00, x".lines());
+        assert_eq!(ids[1], moved[1]);
+        assert_eq!(ids[1].as_ref().map(|id| id.len()), Some(8));
+    }
+
+    #[test]
+    fn ids_search() {
+        assert_eq!(as_id_search("id:3f2a9c01"), Some("3f2a9c01"));
+        assert_eq!(as_id_search("ID:dentist"), Some("dentist"));
+        assert_eq!(as_id_search("dentist"), None);
+        assert_eq!(as_id_search("ü"), None);
+    }
+}
@@ -1,22 +1,43 @@
+mod archive;
+mod check;
 mod color;
+mod edit;
+mod format;
+mod holidays;
+mod ids;
+mod normalize;
 mod parser;
+mod search;
+mod timezone;
 mod utils;
 
+use archive::{
+    ArchiveLayout, ArchiveQuery, UnarchiveSelection, accumulate_archived_termine_by_search,
+    archive_appointments, get_archive_files, print_archive_summary, unarchive_appointments,
+};
+use check::{Problem, check_file};
 use chrono::{Datelike, Utc};
 pub(crate) use chrono::{Days, NaiveDate};
+use chrono_tz::Tz;
 use color::*;
-use parser::{as_date, between, get_termin_from_line, is_date};
+use format::DisplayFormat;
+use ids::{get_id_text, get_line_ids};
+use parser::{
+    as_date, between, expand_placeholders, get_file_header, get_termin_from_line, get_warn_days,
+    is_date, parse_line, parse_period, parse_relative_date,
+};
+use search::{Matcher, get_countdown, get_occurrences, get_search_term};
 use std::{
     env::{self},
-    fs::{self, DirEntry, File, OpenOptions},
+    fs::{self, File},
     io::{self, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
+use timezone::{extract_zone, get_candidate_dates, parse_zone, to_viewer_zone};
 
 use crate::{
     parser::is_day,
-    parser::is_month,
-    utils::{append_line_to_file, ask_option, get_rrem_file_options, remove_line_from_file},
+    utils::{append_line_to_file, ask_option, confirm, get_flag_value, get_rrem_file_options},
 };
 
 /// Simple version of Diane Skoll's `remind` tool
@@ -30,25 +51,49 @@ use crate::{
 
 // If we're not on Linux, don't bother
 const EXIT_CODE_NO_HOME_DIR: i32 = 1;
+const EXIT_CODE_CHECK_FAILED: i32 = 2; // `check` found problems
+const EXIT_CODE_INVALID_INPUT: i32 = 3; // e.g. `add` could not read the appointment
 const ARCHIVE_THRESHOLD: usize = 1; // @todo make threshold configurable
+const WHEN_HORIZON: u64 = 365; // `when` looks this many days ahead for recurring appointments ...
+const WHEN_OCCURRENCES: usize = 3; // ... and lists at most this many occurrences of each
 const VERSION: &str = "0.0.19";
 
 #[derive(Debug)]
 struct RRemindFolders {
     dir_rem_files: String,
     dir_rem_archive: String,
-    #[allow(dead_code)]
-    b_merge_archive: bool, // Should archived appointments be merged into one file? Or kept in separate files?
+    archive_layout: ArchiveLayout, // One archive file per .rem-file, one for everything, or one per year?
+    when_horizon: u64,             // Days `when` looks ahead for recurring appointments
+    when_occurrences: usize,       // Occurrences `when` lists per recurring appointment
+    holidays: String,              // Regions whose public holidays are listed, e.g. DE-BW
+    format: DisplayFormat,         // How dates and times are shown, and written by `add`
+    timezone: String, // Zone appointments are shown in, e.g. Europe/Berlin (empty: the computer's)
+    default_file: String, // .rem-file `add` writes to without asking, e.g. work.rem (empty: ask)
 }
 
 impl RRemindFolders {
     /// Write configuration to file, currently only
     /// rremind_files=<directory>
     /// rremind_archive=<directory>
+    /// rremind_archive_layout=<file|merged|year|year/file>
+    /// rremind_when_horizon=<days>
+    /// rremind_when_occurrences=<n>
+    /// rremind_holidays=<regions>
+    /// rremind_timezone=<zone>
+    /// rremind_default_file=<file>
+    /// and the date and time formats (see format/mod.rs)
     fn to_config_file_text(&self) -> String {
         format!(
-            "rremind_files={}\nrremind_archive={}\n", // <- final line break is important for later parsing
-            self.dir_rem_files, self.dir_rem_archive
+            "rremind_files={}\nrremind_archive={}\nrremind_archive_layout={}\nrremind_when_horizon={}\nrremind_when_occurrences={}\nrremind_holidays={}\nrremind_timezone={}\nrremind_default_file={}\n{}", // <- final line break is important for later parsing
+            self.dir_rem_files,
+            self.dir_rem_archive,
+            self.archive_layout.to_config_value(),
+            self.when_horizon,
+            self.when_occurrences,
+            self.holidays,
+            self.timezone,
+            self.default_file,
+            self.format.to_config_text()
         )
     }
 }
@@ -62,6 +107,11 @@ struct Appointment {
     appointment_description: String,
     appointment_date_alt_text: String,
     appointment_color: Option<String>, // new in 0.15, color is defined on top of file, e.g. #color=red
+    appointment_file: Option<String>, // new in 0.20, the .rem-file an archived appointment came from
+    appointment_days_ahead: Option<i64>, // new in 0.20, set if this is an advance warning (WARN n)
+    appointment_origin_year: Option<i32>, // new in 0.20, first year of a yearly appointment (1980 nov 6 YEARLY)
+    appointment_zone: Option<(chrono_tz::Tz, chrono::NaiveTime)>, // new in 0.20, zone and start time as written, if converted
+    appointment_id: Option<String>, // new in 0.20, see ids/mod.rs; shown with --ids
 }
 
 impl Appointment {
@@ -89,38 +139,14 @@ impl Appointment {
         }
     }
 
-    fn get_time(&self) -> String {
-        if self.appointment_start.is_none() {
-            return "".to_string();
-        }
-        if self.appointment_stop.is_none() {
-            return self.appointment_start.unwrap().format("%H:%M").to_string();
-        }
-        return format!(
-            "{} - {}",
-            self.appointment_start.unwrap().format("%H:%M"),
-            self.appointment_stop.unwrap().format("%H:%M")
-        );
-    }
-
-    pub fn to_rrem_string(&self) -> String {
-        if self.appointment_is_full_date {
-            return format!(
-                "{} {}, {}",
-                self.appointment_date
-                    .unwrap()
-                    .format("%d.%m.%Y")
-                    .to_string(),
-                self.get_time(),
-                self.appointment_description
-            );
-        } else {
-            // panic!("{:?}", self);
-            return format!("{}", self.appointment_date_alt_text);
-            // println!(
-            //     "SORRY, currently only full dates can be saved through the cli. (You can still note periodical dates in rrem-files, though -- see `man rremind`)"
-            // );
-            // return String::from("");
+    /// The description for output: in yearly appointments with
+    /// a first year, {age} and {nth} are replaced by the number of years.
+    fn get_description(&self) -> String {
+        match (self.appointment_origin_year, self.appointment_date) {
+            (Some(origin), Some(dtm)) => {
+                expand_placeholders(&self.appointment_description, dtm.year() - origin)
+            }
+            _ => self.appointment_description.to_owned(),
         }
     }
 }
@@ -136,6 +162,10 @@ enum Command {
     EditConfig,
     AddAppointment,
     Archive,
+    Unarchive,            // new in 0.0.20
+    Format,               // new in 0.0.20
+    Change(edit::Change), // delete, move, edit; new in 0.0.20
+    Open,                 // new in 0.0.20
     Version,
     Unknown,
 }
@@ -146,9 +176,11 @@ enum Command {
 // - append to this file if it exists
 // - create the file if it does not yet exist.
 // - print the earliest date per file that was archived
-// - consider writing an 'undo' (with the earliest date as input)
+// - note every archived line in a journal, so that `unarchive` can undo it
 fn main() {
     let s_rremind_folder = get_rremind_folders(); // from config or from user.
+    holidays::set_regions(&s_rremind_folder.holidays);
+    timezone::set_viewer_zone(&s_rremind_folder.timezone);
     let mut search_term = "".to_string(); // in case this is a 'when?' request.
 
     let args: Vec<String> = env::args().collect();
@@ -159,8 +191,10 @@ fn main() {
     let mut requested_date_start: NaiveDate = chrono::offset::Local::now().date_naive();
     let mut requested_date_stop: NaiveDate = chrono::offset::Local::now().date_naive();
 
+    // --ids only changes how listings look (see below)
+    let command_args: Vec<String> = args.iter().filter(|arg| *arg != "--ids").cloned().collect();
     let cmd = read_user_input(
-        &args,
+        &command_args,
         &mut requested_date_start,
         &mut requested_date_stop,
         &mut search_term,
@@ -186,16 +220,35 @@ fn main() {
         println!(
             "- rremind <n..m>: lists appointments from n days relative to today to m days relative to today (rremind -1..2 lists appointments from yesterday to the day after tomorrow)."
         );
-        println!("- rremind when <term>: lists future appointments containing 'term'");
         println!(
-            "- rremind when_was <term>: lists appointments containing 'term' in *archived* files"
+            "- rremind when <term> [--regex | --fuzzy] [--case-sensitive] [--whole-line]: lists future appointments containing 'term' (ignoring case, in the description only); recurring appointments with their next occurrences"
+        );
+        println!(
+            "- --ids (with any listing): show the id of each appointment, e.g. [id:3f2a9c01], to use with delete, move, edit, open and archive"
+        );
+        println!(
+            "- rremind when_was [<term>] [--file <name>] [--tag <tag>] [--since <dtm>] [--until <dtm>] [--count | --summary]: lists appointments containing 'term' in *archived* files (optionally only those archived from file <name>, tagged #<tag>, or between the given dates; or only count them)"
         );
         println!("- rremind check: report syntax errors in .rem-files.");
         println!("- rremind config: edit folders");
         println!(
-            "- rremind add: add an appointment. Seperate date indication from description with a comma (e.g. `rremind add Mon 13:00, lunch`)"
+            "- rremind add [--file <name>] [--yes]: add an appointment. Seperate date indication from description with a comma (e.g. `rremind add Mon 13:00, lunch`); --file names the .rem-file (default: rremind_default_file in rr.rc, or you are asked), --yes adds it without asking"
+        );
+        println!(
+            "- rremind delete <search> | move <search> <new date/time> | edit <search> [--yes]: delete, reschedule, or rewrite one appointment; <search> is looked for like in `when`, or is the place of the line (work.rem:3) or its id (id:3f2a9c01, see --ids). Asks which one if several match, and before changing the file"
+        );
+        println!(
+            "- rremind open <search | file:line>: open the line in $VISUAL or $EDITOR (at its line number), and check the file afterwards"
+        );
+        println!(
+            "- rremind fmt [--check] [--yes] [file]: rewrite .rem-files in one style and sort their entries (recurring first, then by date); shows the changes first. With --check, only shows them and exits with 2 if a file is not formatted"
+        );
+        println!(
+            "- rremind archive [<search>]: archive appointments that have a specific date in the past (or only the one appointment <search> stands for, e.g. id:3f2a9c01)"
+        );
+        println!(
+            "- rremind unarchive [--last | --since <dtm> | --match <term>]: move archived appointments back (of the last archive run, of all runs since <dtm>, or those matching <term>, as in `when_was`)"
         );
-        println!("- rremind archive: archive appointments that have a specific date in the past");
         return;
     }
 
@@ -205,27 +258,150 @@ fn main() {
     }
 
     if cmd == Command::AddAppointment {
-        add_appointment(&args[2..].join(" ").to_owned());
+        if let Err(e) = add_appointment(&args[2..]) {
+            eprintln!("{e}");
+            std::process::exit(EXIT_CODE_INVALID_INPUT);
+        }
         return;
     }
 
-    let mut accumulated_termine: Vec<Appointment> = vec![];
-    let mut acc_errors: Vec<String> = vec![];
-
-    let directory_with_remind_files = match cmd == Command::SearchArchive{
-        false => fs::read_dir(&s_rremind_folder.dir_rem_files).expect(&format!(
-            "Cannot find folder >{}<, which is supposedly containing the reminder files. Try `rremind config`?",
-            s_rremind_folder.dir_rem_files
-            )),
-        true => {
-            fs::read_dir(&s_rremind_folder.dir_rem_archive).expect(&format!(
-                "Cannot find folder >{}<, which is supposedly containing the reminder archive. Try `rremind config`?",
-                s_rremind_folder.dir_rem_archive))
+    if cmd == Command::Unarchive {
+        match UnarchiveSelection::from_args(&args) {
+            Ok(selection) => unarchive_appointments(&s_rremind_folder.dir_rem_archive, &selection),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(EXIT_CODE_INVALID_INPUT);
+            }
+        }
+        return;
+    }
+
+    if let Command::Change(change) = cmd {
+        if let Err(e) = edit::change_appointment(
+            change,
+            &args,
+            &s_rremind_folder.dir_rem_files,
+            &s_rremind_folder.format.add_date_format,
+        ) {
+            eprintln!("{e}");
+            std::process::exit(EXIT_CODE_INVALID_INPUT);
+        }
+        return;
+    }
+
+    if cmd == Command::Open {
+        match edit::open_in_editor(&args, &s_rremind_folder.dir_rem_files) {
+            Ok(0) => {}
+            Ok(_) => std::process::exit(EXIT_CODE_CHECK_FAILED),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(EXIT_CODE_INVALID_INPUT);
+            }
+        }
+        return;
+    }
+
+    if cmd == Command::Format {
+        // rremind fmt [--check] [--yes] [file]
+        let check_only = args.iter().any(|arg| arg == "--check");
+        let yes = args.iter().any(|arg| arg == "--yes");
+        let name = args[2..].iter().find(|arg| !arg.starts_with("--"));
+        let files = match normalize::get_rem_files(
+            &s_rremind_folder.dir_rem_files,
+            name.map(|n| n.as_str()),
+        ) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(EXIT_CODE_CHECK_FAILED);
             }
         };
+        let unformatted = normalize::format_files(
+            &files,
+            &s_rremind_folder.format.add_date_format,
+            check_only,
+            yes,
+        );
+        match (unformatted, check_only) {
+            (0, _) => println!("All files are formatted."),
+            (n, true) => {
+                println!("{n} file(s) would be rewritten by `rremind fmt`.");
+                std::process::exit(EXIT_CODE_CHECK_FAILED);
+            }
+            (n, false) => println!("{n} file(s) left as they were."),
+        }
+        return;
+    }
+
+    // All lines archived in this run share the timestamp in the journal
+    let archive_run = chrono::offset::Local::now()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+
+    // rremind archive <search>: only this appointment, e.g. `archive id:3f2a9c01`
+    if cmd == Command::Archive
+        && let Some(search) = edit::get_words(&args).first().copied()
+    {
+        let found = match edit::find_one(
+            search,
+            &args,
+            &s_rremind_folder.dir_rem_files,
+            "archive",
+            false,
+        ) {
+            Ok(found) => found,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(EXIT_CODE_INVALID_INPUT);
+            }
+        };
+        let datei = Path::new(&s_rremind_folder.dir_rem_files).join(&found.file);
+        archive_appointments(
+            &datei,
+            &fs::read_to_string(&datei).unwrap_or_default(),
+            &s_rremind_folder.dir_rem_archive,
+            s_rremind_folder.archive_layout,
+            &archive_run,
+            Some(found.line_no),
+        );
+        return;
+    }
+
+    let mut accumulated_termine: Vec<Appointment> = vec![];
+    let mut acc_errors: Vec<Problem> = vec![];
+
+    let matcher = match Matcher::from_args(&search_term, &args) {
+        Ok(m) => m,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+    let archive_query = match ArchiveQuery::from_args(&args) {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(EXIT_CODE_INVALID_INPUT);
+        }
+    };
+
+    let directory_with_remind_files: Vec<PathBuf> = match cmd == Command::SearchArchive {
+        false => fs::read_dir(&s_rremind_folder.dir_rem_files)
+            .unwrap_or_else(|_| {
+                panic!(
+                    "Cannot find folder >{}<, which is supposedly containing the reminder files. Try `rremind config`?",
+                    s_rremind_folder.dir_rem_files
+                )
+            })
+            .flatten()
+            .map(|datei| datei.path())
+            .collect(),
+        // Only the archive files of the requested years
+        true => get_archive_files(&s_rremind_folder.dir_rem_archive, &archive_query),
+    };
 
     fn get_color(s_in: &str) -> Option<String> {
-        let candidate = &s_in[s_in.find("=").unwrap_or_default() + 1..].to_lowercase()[..];
+        let candidate = &s_in.to_lowercase()[..];
         match candidate {
             "red" => Some(COLOR_BRIGHT_RED.to_owned()),
             "yellow" => Some(COLOR_YELLOW.to_owned()),
@@ -236,222 +412,299 @@ fn main() {
         }
     }
 
-    for path in directory_with_remind_files {
-        if let Ok(datei) = path {
-            let as_str = datei.path().to_str().unwrap().to_owned();
-            if as_str.ends_with(&".rem")
-                || (cmd == Command::SearchArchive && as_str.ends_with("done"))
-            {
-                let termine_aus_datei = std::fs::read_to_string(datei.path()).unwrap();
-                let color = match termine_aus_datei.starts_with("# color=") {
-                    true => get_color(termine_aus_datei.lines().rev().last().unwrap()),
-                    false => None,
-                };
-
-                match cmd {
-                    Command::Archive => archive_appointments(
-                        &datei,
-                        &termine_aus_datei,
-                        &s_rremind_folder.dir_rem_archive,
-                    ),
-                    Command::ListAppointments => accumulate_termine(
-                        requested_date_start,
-                        &termine_aus_datei,
-                        color,
-                        &mut accumulated_termine,
-                    ),
-                    Command::MultiListAppointments => {
-                        let mut iter_date = requested_date_start;
-                        while iter_date <= requested_date_stop {
-                            accumulate_termine(
-                                iter_date,
-                                &termine_aus_datei,
-                                color.to_owned(),
-                                &mut accumulated_termine,
-                            );
-                            add_or_subtract_days(&mut iter_date, 1);
-                        }
-                    }
-                    Command::SearchAppointments | Command::SearchArchive => {
-                        accumulate_termine_by_search(
-                            &search_term,
-                            &termine_aus_datei,
+    for datei in directory_with_remind_files {
+        let as_str = datei.to_str().unwrap().to_owned();
+        if as_str.ends_with(&".rem") || (cmd == Command::SearchArchive && as_str.ends_with("done"))
+        {
+            let termine_aus_datei = std::fs::read_to_string(&datei).unwrap();
+            // The lines of the file with their ids (see ids/mod.rs)
+            let file_name = datei.file_name().unwrap().to_string_lossy().to_string();
+            let lines: Vec<(&str, Option<String>)> = termine_aus_datei
+                .lines()
+                .zip(get_line_ids(&file_name, termine_aus_datei.lines()))
+                .collect();
+            let color = match get_file_header(&termine_aus_datei, "color") {
+                Some(c) => get_color(&c),
+                None => None,
+            };
+            // Default advance warning for all appointments in the file, e.g. `# warn=3`
+            let warn_days =
+                get_file_header(&termine_aus_datei, "warn").and_then(|w| w.parse::<u64>().ok());
+            // Default time zone of the file, e.g. `# tz=America/New_York`
+            let zone = get_file_header(&termine_aus_datei, "tz").and_then(|z| parse_zone(&z));
+
+            match cmd {
+                Command::Archive => archive_appointments(
+                    &datei,
+                    &termine_aus_datei,
+                    &s_rremind_folder.dir_rem_archive,
+                    s_rremind_folder.archive_layout,
+                    &archive_run,
+                    None,
+                ),
+                Command::ListAppointments => accumulate_termine(
+                    requested_date_start,
+                    &lines,
+                    color,
+                    warn_days,
+                    zone,
+                    &mut accumulated_termine,
+                ),
+                Command::MultiListAppointments => {
+                    let mut iter_date = requested_date_start;
+                    while iter_date <= requested_date_stop {
+                        accumulate_termine(
+                            iter_date,
+                            &lines,
                             color.to_owned(),
+                            warn_days,
+                            zone,
                             &mut accumulated_termine,
-                        )
+                        );
+                        add_or_subtract_days(&mut iter_date, 1);
                     }
-                    Command::Check => accumulate_syntax_errors(
-                        datei.path().to_str().unwrap(),
-                        &termine_aus_datei,
-                        &mut acc_errors,
-                    ),
-                    _ => panic! {"Command misunderstood, sorry."},
                 }
+                Command::SearchArchive => accumulate_archived_termine_by_search(
+                    &matcher,
+                    &archive_query,
+                    datei.file_name().unwrap().to_str().unwrap(),
+                    &termine_aus_datei,
+                    &mut accumulated_termine,
+                ),
+                Command::SearchAppointments => accumulate_termine_by_search(
+                    &matcher,
+                    &lines,
+                    color.to_owned(),
+                    requested_date_start,
+                    zone,
+                    &s_rremind_folder,
+                    &mut accumulated_termine,
+                ),
+                Command::Check => {
+                    acc_errors.extend(check_file(datei.to_str().unwrap(), &termine_aus_datei))
+                }
+                _ => panic! {"Command misunderstood, sorry."},
             }
         }
     }
 
+    if cmd == Command::ListAppointments || cmd == Command::MultiListAppointments {
+        let holidays_until = match cmd == Command::MultiListAppointments {
+            true => requested_date_stop,
+            false => requested_date_start,
+        };
+        accumulate_holidays(
+            requested_date_start,
+            holidays_until,
+            &mut accumulated_termine,
+        );
+    }
+
+    if cmd == Command::SearchArchive {
+        if args.iter().any(|arg| arg == "--count") {
+            println!("{}", accumulated_termine.len());
+            return;
+        }
+        if args.iter().any(|arg| arg == "--summary") {
+            print_archive_summary(&accumulated_termine);
+            return;
+        }
+    }
+
     if cmd == Command::Check {
         if acc_errors.is_empty() {
             println!("Check complete: all ok!");
         } else {
-            for problem in acc_errors {
-                println!("Problem: \n{}\n", problem);
+            for problem in &acc_errors {
+                println!("{}\n", problem);
             }
+            println!("Check complete: {} problem(s) found.", acc_errors.len());
+            std::process::exit(EXIT_CODE_CHECK_FAILED);
         }
         return;
     }
 
     sortiere(&mut accumulated_termine);
 
+    // --ids shows the id of each appointment, for delete, move, edit, ...
+    let show_ids = args.iter().any(|arg| arg == "--ids");
+    let id_text = |termin: &Appointment| match show_ids {
+        true => get_id_text(&termin.appointment_id),
+        false => "".to_string(),
+    };
+
+    if matcher.is_fuzzy() {
+        print_ranked(
+            &matcher,
+            &mut accumulated_termine,
+            &s_rremind_folder.format,
+            show_ids,
+        );
+        return;
+    }
+
     let mut new_date: Option<NaiveDate> = None;
     println!("\n");
     for t in accumulated_termine {
         if let Some(dtm) = t.appointment_date {
+            // `when` says how far ahead the appointment is
+            let countdown = match cmd == Command::SearchAppointments {
+                true => get_countdown(dtm, requested_date_start),
+                false => "".to_string(),
+            };
             if new_date.is_none() {
                 new_date = Some(dtm);
                 println!(
-                    "  {} ({})\n  ================",
-                    dtm.to_string(),
-                    dtm.weekday()
+                    "  {}{}\n  ================",
+                    s_rremind_folder.format.format_date(dtm),
+                    countdown
                 );
             } else {
                 if new_date.unwrap() != dtm {
                     new_date = Some(dtm);
                     println!(
-                        "\n  {} ({})\n  ================",
-                        dtm.to_string(),
-                        dtm.weekday()
+                        "\n  {}{}\n  ================",
+                        s_rremind_folder.format.format_date(dtm),
+                        countdown
                     );
                 }
             }
         }
         if t.appointment_color.is_some() {
             println!(
-                "{}- {}{}{}",
+                "{}- {}{}{}{}{}{}",
                 t.appointment_color.as_ref().unwrap(),
-                t.appointment_description,
-                get_zeitangabe(&t),
+                t.get_description(),
+                get_zeitangabe(&t, &s_rremind_folder.format),
+                get_vorwarnung(&t),
+                get_dateiangabe(&t),
+                id_text(&t),
                 COLOR_RESET
             );
         } else {
-            println!("- {}{}", t.appointment_description, get_zeitangabe(&t),);
+            println!(
+                "- {}{}{}{}{}",
+                t.get_description(),
+                get_zeitangabe(&t, &s_rremind_folder.format),
+                get_vorwarnung(&t),
+                get_dateiangabe(&t),
+                id_text(&t)
+            );
         }
     }
 }
 
-fn seems_parseable(s_info: &str) -> Option<bool> {
-    let words: Vec<&str> = s_info.split_whitespace().collect();
-    if is_day(words.get(0)?) {
-        return Some(true);
-    } // <- courageous or stupid?
-    if is_month(words.get(0)?) {
-        return Some(true);
-    } // <- courageous or stupid?
-    None
+// One end of a range: days relative to today (-1), a date (2025-5-1,
+// 1.5.2025), a day in words (today, fri), or a period (2025-05: its
+// first day at the start of the range, its last day at the end)
+fn get_range_end(s_in: &str, today: NaiveDate, is_end: bool) -> Option<NaiveDate> {
+    if let Ok(days) = s_in.parse::<i64>() {
+        let mut datum = today;
+        add_or_subtract_days(&mut datum, days);
+        return Some(datum);
+    }
+    if let Some(datum) = as_date(s_in) {
+        return Some(datum);
+    }
+    if let Some((datum, 1)) = parse_relative_date(&[s_in], today) {
+        return Some(datum);
+    }
+    let (start, stop) = parse_period(s_in, today)?;
+    match is_end {
+        true => Some(stop),
+        false => Some(start),
+    }
 }
-fn add_appointment(s_info: &str) {
-    // println!("Adding this: >{s_info}<");
-    let f_path = format!(
-        "{}/{}",
-        get_rremind_folders().dir_rem_files,
-        ask_option(
-            "In which file should the appointment go? ",
-            get_rrem_file_options(),
-        )
-    );
-
-    match get_termin_from_line(s_info, None) {
-        Some(app) => {
-            if app.appointment_is_full_date {
-                append_line_to_file(&f_path, &app.to_rrem_string());
-            } else {
-                if let Some(b) = seems_parseable(s_info) {
-                    if b {
-                        append_line_to_file(&f_path, s_info);
-                    }
-                } else {
-                    panic!("Sorry, I didn't understand your input.");
-                }
-            }
-        }
 
-        None => {
-            if let Some(b) = seems_parseable(s_info) {
-                if b {
-                    append_line_to_file(&f_path, s_info);
-                }
-            } else {
-                panic!("Sorry, I didn't understand your input.");
-            }
+// `add next fri 10:00, haircut` is written with the full date
+// (`23.10.2026 10:00, haircut`); a weekday on its own
+// (`add Mon 13:00, lunch`) remains a weekly appointment.
+fn resolve_relative_date(s_info: &str, today: NaiveDate) -> String {
+    let words: Vec<&str> = s_info.split_whitespace().collect();
+    match parse_relative_date(&words, today) {
+        Some((_, 1)) if is_day(words[0].trim_end_matches(',')) => s_info.to_string(),
+        Some((datum, used)) => {
+            let comma = match words[used - 1].ends_with(',') {
+                true => ",",
+                false => "",
+            };
+            let mut line = vec![format!("{}{comma}", datum.format("%d.%m.%Y"))];
+            line.extend(words[used..].iter().map(|w| w.to_string()));
+            line.join(" ")
         }
-    };
-
-    // dbg!("{}", app);
-
-    // let d = ask("Which date? ", None);
-    // let app_date = parse_user_input(d).unwrap();
-
-    // let desc = ask("What's the appointments text? ");
-
-    // add_appointment(&f_path, normalize(&app_date, &desc));
-    println!("OK")
+        None => s_info.to_string(),
+    }
 }
 
-fn archive_appointments(file_name: &DirEntry, contents: &str, archiv_folder: &str) {
-    std::fs::create_dir_all(&archiv_folder).expect(&format!(
-        "Cannot create archive directory `{archiv_folder}`"
-    ));
-
-    let binding = file_name.path();
-
-    let archive_name = format!(
-        "{}{}.done",
-        &archiv_folder,
-        &binding.file_stem().unwrap().to_str().unwrap()
-    );
-
-    println!(
-        "\nLooking for items to archive in {}...",
-        file_name.path().as_os_str().to_str().unwrap()
-    );
-
-    let mut b_found = false;
-    for line in contents.lines() {
-        match get_termin_from_line(&line, None) {
-            Some(termin) => {
-                if termin.is_past() {
-                    println!(" - {line}");
-                    b_found = true;
-                    archive_appointment(&line, &file_name, &archive_name);
-                }
+// rremind add [--file <name>] [--yes] <appointment>: the appointment is
+// read like a line of a .rem-file (after resolving "next fri" and the
+// like) and written in canonical form. Without --file, it goes to
+// rremind_default_file (rr.rc), or to the file the user picks; without
+// --yes, the user confirms the line first.
+fn add_appointment(args: &[String]) -> Result<(), String> {
+    let config = get_rremind_folders();
+    let yes = args.iter().any(|arg| arg == "--yes");
+    let mut words: Vec<&str> = vec![];
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--file" => {
+                rest.next();
             }
-            _ => {} // No interpretable line -> no action
+            "--yes" => {}
+            word => words.push(word),
         }
     }
-
-    if !b_found {
-        println!(" - no items to archive");
+    let s_info = resolve_relative_date(&words.join(" "), chrono::offset::Local::now().date_naive());
+    let spec = parse_line(&s_info)
+        .map_err(|e| format!("Cannot add `{s_info}`: {e}\n  try: {}", e.suggestion))?;
+    if spec.description.is_none() {
+        return Err(format!(
+            "Cannot add `{s_info}`: there is no description. Separate it from the date with a comma, e.g. `rremind add Mon 13:00, lunch`"
+        ));
     }
-}
+    let line = spec
+        .to_line_with_ignored(&config.format.add_date_format)
+        .map_err(|e| {
+            format!("Cannot add `{s_info}`: {e}; put them into the description, after the comma")
+        })?;
+
+    let files = get_rrem_file_options();
+    let default_file = Some(config.default_file.clone()).filter(|name| !name.is_empty());
+    let file = match get_flag_value(args, "--file").or(default_file) {
+        Some(name) => {
+            let name = match name.ends_with(".rem") {
+                true => name,
+                false => format!("{name}.rem"),
+            };
+            if !files.contains(&name) {
+                return Err(format!(
+                    "There is no file `{name}` in {}; the files are: {}",
+                    config.dir_rem_files,
+                    files.join(", ")
+                ));
+            }
+            name
+        }
+        None if yes => {
+            return Err(
+                "In which file should the appointment go? Use `--file <name>`, or set `rremind_default_file=<name>` in rr.rc.".to_string(),
+            );
+        }
+        None => ask_option("In which file should the appointment go? ", files)
+            .ok_or("Sorry, I didn't understand your choice; nothing was added.")?,
+    };
 
-fn archive_appointment(line: &str, file_name: &&DirEntry, archive_name: &str) {
-    if append_line_to_file(archive_name, line) {
-        remove_line_from_file(file_name.path().as_os_str().to_str().unwrap(), line);
+    if !yes && !confirm(&format!("Add `{line}` to {file}? (y/n)"), true) {
+        println!("Nothing added.");
+        return Ok(());
     }
-}
-
-fn add_line_to_archive_file(archive_name: &str, line: &str) {
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open(archive_name)
-        .unwrap();
-
-    if let Err(e) = writeln!(file, "{}", line) {
-        println!("Couldn't write to file: {}", e);
+    let f_path = Path::new(&config.dir_rem_files).join(&file);
+    if !append_line_to_file(&f_path.to_string_lossy(), &line) {
+        return Err(format!("Cannot write to {}", f_path.display()));
     }
+    println!("Added to {file}: {line}");
+    Ok(())
 }
 
 /// Ask for directory containing the .rem-files,
@@ -462,7 +715,7 @@ fn add_line_to_archive_file(archive_name: &str, line: &str) {
 /// more complicated, and you can achieve the same through
 /// symlinks
 ///
-/// @todo: Optionally, archived appointments should be merged
+/// Optionally, archived appointments are merged
 /// into *one* archive file, though.
 ///
 /// @todo: Ask for ARCHIVE_THRESHOLD (how many days ago do
@@ -489,10 +742,17 @@ fn edit_config() -> RRemindFolders {
         &s_folders.dir_rem_archive,
     );
 
+    let line_layout = get_user_input(
+        "How should the archive be organised? One file per .rem-file (file), one file for everything (merged), one file per year (year), or one folder per year with one file per .rem-file (year/file)?",
+        s_folders.archive_layout.to_config_value(),
+    );
+
     let ret = RRemindFolders {
         dir_rem_files: line_dir.trim().to_string(),
         dir_rem_archive: line_archive.trim().to_string(),
-        b_merge_archive: false, // <- @todo
+        archive_layout: ArchiveLayout::from_config_value(&line_layout)
+            .unwrap_or(s_folders.archive_layout),
+        ..s_folders // the rest is not asked for, but can be edited in rr.rc
     };
 
     let mut f = std::fs::OpenOptions::new()
@@ -529,48 +789,152 @@ fn get_user_input(question: &str, default: &str) -> String {
     }
 }
 
-fn accumulate_syntax_errors(pfad: &str, termine_aus_datei: &str, acc_errors: &mut Vec<String>) {
-    for line in termine_aus_datei.lines() {
-        if !line.starts_with("# ") && line.is_empty() && get_termin_from_line(&line, None).is_none()
-        {
-            acc_errors.push(format!("File: '{}':\nLine: {}\n", pfad, line));
-        }
-    }
-}
-
+/// Appointments on `datum` -- and advance warnings of appointments
+/// in the next days (`WARN n` in the line, or `warn_days` for the dated and
+/// yearly appointments of the whole file).
+/// `lines` are the lines of a .rem-file with their ids.
 fn accumulate_termine(
     datum: chrono::NaiveDate,
-    termine_aus_datei: &str,
+    lines: &[(&str, Option<String>)],
     color: Option<String>,
+    warn_days: Option<u64>,
+    zone: Option<Tz>,
     termine: &mut Vec<Appointment>,
 ) {
-    for line in termine_aus_datei.lines() {
-        if let Some(mut termin_match) = get_termin_from_line(&line, Some(datum)) {
-            // <-- // @todo Nov 17, 2024: Really? Some(datum)? Doing this simply to compile
+    for (line, id) in lines {
+        let line_zone = extract_zone(line).or(zone);
+        if let Some(mut termin_match) = get_termin_on(line, datum, line_zone) {
             termin_match.appointment_color = color.to_owned();
-            if termin_match.appointment_date == Some(datum) {
-                termine.push(termin_match);
+            termin_match.appointment_id = id.clone();
+            termine.push(termin_match);
+            continue;
+        }
+        if get_termin_from_line(line, Some(datum)).is_some() {
+            let lead = get_warn_days(line, warn_days).unwrap_or_default();
+            for days_ahead in 1..=lead {
+                let mut ahead = datum;
+                add_or_subtract_days(&mut ahead, days_ahead as i64);
+                // converted to the viewer's zone like on the day itself
+                if let Some(mut warning) = get_termin_on(line, ahead, line_zone) {
+                    warning.appointment_color = color.to_owned();
+                    warning.appointment_id = id.clone();
+                    warning.appointment_date = Some(datum);
+                    warning.appointment_days_ahead = Some(days_ahead as i64);
+                    termine.push(warning);
+                    break;
+                }
             }
         }
     }
 }
 
+// The appointment in `line` on `datum`, if there is one. With a time
+// zone, an occurrence on the day before or after may move to `datum`.
+fn get_termin_on(line: &str, datum: NaiveDate, zone: Option<Tz>) -> Option<Appointment> {
+    let Some(zone) = zone else {
+        return get_termin_from_line(line, Some(datum))
+            .filter(|termin| termin.appointment_date == Some(datum));
+    };
+    get_candidate_dates(datum).into_iter().find_map(|base| {
+        let mut termin = get_termin_from_line(line, Some(base))
+            .filter(|termin| termin.appointment_date == Some(base))?;
+        to_viewer_zone(&mut termin, zone);
+        (termin.appointment_date == Some(datum)).then_some(termin)
+    })
+}
+
+/// Public holidays (of the regions in rr.rc) from `start` until `stop`,
+/// as all-day appointments
+fn accumulate_holidays(start: NaiveDate, stop: NaiveDate, termine: &mut Vec<Appointment>) {
+    let mut datum = start;
+    while datum <= stop {
+        for name in holidays::get_holidays(datum) {
+            termine.push(Appointment {
+                appointment_date: Some(datum),
+                appointment_is_full_date: true,
+                appointment_start: None,
+                appointment_stop: None,
+                appointment_description: name.to_string(),
+                appointment_date_alt_text: datum.to_string(),
+                appointment_color: Some(COLOR_MAGENTA.to_owned()),
+                appointment_file: None,
+                appointment_days_ahead: None,
+                appointment_origin_year: None,
+                appointment_zone: None,
+                appointment_id: None,
+            });
+        }
+        add_or_subtract_days(&mut datum, 1);
+    }
+}
+
+/// Appointments matching the search, from `today` on:
+/// appointments with a full date if they are not past,
+/// recurring appointments with their next occurrences
+/// (as many as configured, within the configured horizon).
+/// `lines` are the lines of a .rem-file with their ids.
 fn accumulate_termine_by_search(
-    search: &String,
-    termine_aus_datei: &str,
+    matcher: &Matcher,
+    lines: &[(&str, Option<String>)],
     color: Option<String>,
+    today: NaiveDate,
+    zone: Option<Tz>,
+    config: &RRemindFolders,
     termine: &mut Vec<Appointment>,
 ) {
-    for line in termine_aus_datei.lines() {
-        if line.contains(search) {
-            if let Some(mut found) = get_termin_from_line(&line, None) {
-                found.appointment_color = color.clone();
-                termine.push(found);
+    for (line, id) in lines {
+        if let Some(found) = get_termin_from_line(line, None)
+            && matcher.matches(line, &found.appointment_description)
+        {
+            let occurrences = get_occurrences(
+                line,
+                today,
+                config.when_horizon,
+                config.when_occurrences,
+                extract_zone(line).or(zone),
+            );
+            for mut next in occurrences {
+                next.appointment_color = color.clone();
+                next.appointment_id = id.clone();
+                termine.push(next);
             }
         }
     }
 }
 
+// Fuzzy search results: best match first, each with its date (and id)
+fn print_ranked(
+    matcher: &Matcher,
+    termine: &mut [Appointment],
+    format: &DisplayFormat,
+    show_ids: bool,
+) {
+    termine.sort_by_key(|t| std::cmp::Reverse(matcher.score(&t.appointment_description)));
+    println!("\n");
+    for t in termine.iter() {
+        let datum = match t.appointment_date {
+            Some(dtm) => format!("{}: ", format.format_date(dtm)),
+            None => "".to_string(),
+        };
+        println!(
+            "{}- {}{}{}{}{}{}",
+            t.appointment_color.as_deref().unwrap_or_default(),
+            datum,
+            t.get_description(),
+            get_zeitangabe(t, format),
+            get_dateiangabe(t),
+            match show_ids {
+                true => get_id_text(&t.appointment_id),
+                false => "".to_string(),
+            },
+            match t.appointment_color.is_some() {
+                true => COLOR_RESET,
+                false => "",
+            }
+        );
+    }
+}
+
 // @todo: unwrap
 fn add_or_subtract_days(datum: &mut NaiveDate, days: i64) {
     if days > 0 {
@@ -609,12 +973,17 @@ fn read_user_input(
         return Command::AddAppointment;
     }
 
+    // -1..2, 2025-05-01..2025-05-31, today..31.5.2025, 2025-05..2025-07
     if argument1.contains("..") {
         match argument1.split_once("..") {
             Some((from, to)) => {
-                if let (Ok(days_start), Ok(days_stop)) = (from.parse::<i64>(), to.parse::<i64>()) {
-                    add_or_subtract_days(datum_start, days_start);
-                    add_or_subtract_days(datum_stop, days_stop);
+                let today = *datum_start;
+                if let (Some(start), Some(stop)) = (
+                    get_range_end(from, today, false),
+                    get_range_end(to, today, true),
+                ) {
+                    *datum_start = start;
+                    *datum_stop = stop;
                     return Command::MultiListAppointments;
                 } else {
                     // Command not intelligible
@@ -626,6 +995,15 @@ fn read_user_input(
         }
     }
 
+    // this-week, next-month, 2025-W20, 2025-05, Q3
+    if argument2.is_empty()
+        && let Some((start, stop)) = parse_period(argument1, *datum_start)
+    {
+        *datum_start = start;
+        *datum_stop = stop;
+        return Command::MultiListAppointments;
+    }
+
     if argument2.is_empty()
         && let Ok(days) = argument1.parse::<i64>()
     {
@@ -633,8 +1011,8 @@ fn read_user_input(
         return Command::ListAppointments;
     } else {
         if argument1 == "when" {
-            if !argument2.is_empty() {
-                *search = argument2.to_owned();
+            if let Some(term) = get_search_term(args) {
+                *search = term;
             } else {
                 panic!(
                     "If you're calling 'when', you need a second parameter. `rremind when dentist`"
@@ -644,7 +1022,10 @@ fn read_user_input(
         }
         if argument1 == "when_was" {
             if !argument2.is_empty() {
-                *search = argument2.to_owned();
+                // `rremind when_was --tag health` works without search term
+                if let Some(term) = get_search_term(args) {
+                    *search = term;
+                }
             } else {
                 panic!(
                     "If you're calling 'when' or 'when_was', you need a second parameter. `rremind when_was dentist`"
@@ -668,12 +1049,38 @@ fn read_user_input(
         if argument1 == "archive" {
             return Command::Archive;
         }
+        if argument1 == "unarchive" {
+            return Command::Unarchive;
+        }
+        if argument1 == "delete" {
+            return Command::Change(edit::Change::Delete);
+        }
+        if argument1 == "move" {
+            return Command::Change(edit::Change::Move);
+        }
+        if argument1 == "edit" {
+            return Command::Change(edit::Change::Edit);
+        }
+        if argument1 == "open" {
+            return Command::Open;
+        }
+        if argument1 == "fmt" {
+            return Command::Format;
+        }
         if argument1 == "config" {
             return Command::EditConfig;
         }
         if argument1 == "help" {
             return Command::Help;
         }
+        // rremind tomorrow, rremind next fri, rremind in 3 weeks
+        let words: Vec<&str> = args[1..].iter().map(|arg| arg.as_str()).collect();
+        if let Some((datum, used)) = parse_relative_date(&words, *datum_start)
+            && used == words.len()
+        {
+            *datum_start = datum;
+            return Command::ListAppointments;
+        }
 
         return Command::Unknown;
     }
@@ -693,10 +1100,32 @@ fn get_rremind_folders() -> RRemindFolders {
             let cfile = std::fs::read_to_string(home_dir).unwrap();
             let dir_rem_files = between(&cfile, "rremind_files=", "\n").to_string();
             let dir_rem_archive = between(&cfile, "rremind_archive=", "\n").to_string();
+            // rremind_merge_archive=true is how 'merged' used to be configured
+            let archive_layout = match ArchiveLayout::from_config_value(between(
+                &cfile,
+                "rremind_archive_layout=",
+                "\n",
+            )) {
+                Some(layout) => layout,
+                None => match between(&cfile, "rremind_merge_archive=", "\n") == "true" {
+                    true => ArchiveLayout::Merged,
+                    false => ArchiveLayout::PerFile,
+                },
+            };
             RRemindFolders {
                 dir_rem_files,
                 dir_rem_archive,
-                b_merge_archive: false, // @todo
+                archive_layout,
+                when_horizon: between(&cfile, "rremind_when_horizon=", "\n")
+                    .parse::<u64>()
+                    .unwrap_or(WHEN_HORIZON),
+                when_occurrences: between(&cfile, "rremind_when_occurrences=", "\n")
+                    .parse::<usize>()
+                    .unwrap_or(WHEN_OCCURRENCES),
+                holidays: between(&cfile, "rremind_holidays=", "\n").to_string(),
+                format: DisplayFormat::from_config(&cfile),
+                timezone: between(&cfile, "rremind_timezone=", "\n").to_string(),
+                default_file: between(&cfile, "rremind_default_file=", "\n").to_string(),
             }
         } else {
             fs::create_dir_all(home_dir.parent().unwrap()).expect("Directory cannot be created.");
@@ -712,23 +1141,33 @@ fn get_rremind_folders() -> RRemindFolders {
     }
 }
 
-fn get_zeitangabe(termin: &Appointment) -> String {
-    if termin.appointment_start.is_none() {
-        return "".to_string();
+// " (21.00 Uhr)", and the time as written if it was in another zone:
+// " (21.00 Uhr) [15.00 America/New_York]"
+fn get_zeitangabe(termin: &Appointment, format: &DisplayFormat) -> String {
+    let zone = match termin.appointment_zone {
+        Some((tz, start)) => format!(" [{} {}]", format.format_time(start), tz.name()),
+        None => "".to_string(),
+    };
+    format!(
+        "{}{zone}",
+        format.format_period(termin.appointment_start, termin.appointment_stop)
+    )
+}
+
+// Advance warnings (WARN n) say when the appointment is
+fn get_vorwarnung(termin: &Appointment) -> String {
+    match termin.appointment_days_ahead {
+        Some(1) => " (tomorrow)".to_string(),
+        Some(days) => format!(" (in {days} days)"),
+        None => "".to_string(),
     }
-    if termin.appointment_stop.is_none() {
-        return format!(
-            " ({} Uhr)",
-            termin.appointment_start.unwrap().format("%H.%M")
-        )
-        .to_string();
-    } else {
-        return format!(
-            " ({}-{} Uhr)",
-            termin.appointment_start.unwrap().format("%H.%M"),
-            termin.appointment_stop.unwrap().format("%H.%M")
-        )
-        .to_string();
+}
+
+// Archived appointments remember the file they came from
+fn get_dateiangabe(termin: &Appointment) -> String {
+    match &termin.appointment_file {
+        Some(datei) => format!(" [{datei}]"),
+        None => "".to_string(),
     }
 }
 
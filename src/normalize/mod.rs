@@ -0,0 +1,281 @@
+// `rremind fmt [--check] [--yes] [file]`: rewrites .rem-files in the
+// canonical form of parser/canonical.rs and sorts them.
+//
+// The header of a file (the comment lines at its top, e.g. `# color=red`)
+// stays where it is; so do blank lines and the comments that open a
+// group of lines between blank lines. Within a group, the entries are
+// sorted: recurring appointments first (weekly, yearly, Easter and
+// Advent), then those with a full date, chronologically. A comment right
+// above an entry moves with it. Lines that cannot be read are left as
+// they are, at the end of their group; so are lines with words that
+// rremind ignores (they would get lost), but these are sorted.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, NaiveTime};
+
+use crate::{
+    parser::{Anchor, DateSpec, LineSpec, parse_line},
+    utils::{confirm, write_lines_to_file},
+};
+
+// An entry of a .rem-file with the comments right above it
+struct Entry {
+    comments: Vec<String>,
+    line: String,
+    key: Option<(u8, i64, Option<NaiveTime>)>, // None: cannot be read
+}
+
+/// The lines of a .rem-file as `rremind fmt` writes them; full dates
+/// in `date_format`
+pub(crate) fn format_lines(contents: &str, date_format: &str) -> Vec<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let header_len = lines
+        .iter()
+        .take_while(|line| line.starts_with('#'))
+        .count();
+    let mut formatted: Vec<String> = lines[..header_len]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+    let mut group: Vec<&str> = vec![];
+    for line in &lines[header_len..] {
+        if line.trim().is_empty() {
+            formatted.extend(sort_group(&group, date_format));
+            formatted.push(String::new());
+            group.clear();
+        } else {
+            group.push(line);
+        }
+    }
+    formatted.extend(sort_group(&group, date_format));
+    formatted
+}
+
+// Lines between blank lines, rewritten and sorted; comments at the top
+// of the group are its heading and stay there
+fn sort_group(group: &[&str], date_format: &str) -> Vec<String> {
+    let heading_len = group
+        .iter()
+        .take_while(|line| line.starts_with('#'))
+        .count();
+    let mut entries: Vec<Entry> = vec![];
+    let mut comments: Vec<String> = vec![];
+    for line in &group[heading_len..] {
+        if line.starts_with('#') {
+            comments.push(line.to_string());
+            continue;
+        }
+        // A line with words the grammar ignores ("Uhr", room numbers)
+        // would lose them; it is sorted, but not rewritten
+        let (line, key) = match parse_line(line) {
+            Ok(spec) if spec.ignored.is_empty() => {
+                (spec.to_line(date_format), Some(get_sort_key(&spec)))
+            }
+            Ok(spec) => (line.to_string(), Some(get_sort_key(&spec))),
+            Err(_) => (line.to_string(), None),
+        };
+        entries.push(Entry {
+            comments: std::mem::take(&mut comments),
+            line,
+            key,
+        });
+    }
+    entries.sort_by_key(|entry| (entry.key.is_none(), entry.key));
+
+    let mut sorted: Vec<String> = group[..heading_len]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+    for entry in entries {
+        sorted.extend(entry.comments);
+        sorted.push(entry.line);
+    }
+    sorted.extend(comments); // comments at the end of the group stay there
+    sorted
+}
+
+// Weekly appointments by their first weekday, yearly ones by month and
+// day, Easter and Advent by their distance from Easter (Advent is about
+// 240 days later), then full dates; appointments on the same day by time
+fn get_sort_key(spec: &LineSpec) -> (u8, i64, Option<NaiveTime>) {
+    let (group, position) = match &spec.date {
+        DateSpec::Weekly(weekdays) => (
+            0,
+            weekdays
+                .first()
+                .map_or(0, |day| day.num_days_from_monday() as i64),
+        ),
+        DateSpec::Yearly { month, day } => (1, (month * 100 + day) as i64),
+        DateSpec::Anniversary(origin) => (1, (origin.month() * 100 + origin.day()) as i64),
+        DateSpec::Anchor(anchor, offset) => match anchor {
+            Anchor::Easter => (2, *offset),
+            Anchor::Advent(n) => (2, 200 + 7 * *n as i64 + offset),
+        },
+        DateSpec::Full(datum) => (3, datum.num_days_from_ce() as i64),
+    };
+    (group, position, spec.start)
+}
+
+/// The lines that differ between `old` and `new`, "-" for removed, "+"
+/// for added lines (along the longest common subsequence)
+pub(crate) fn get_diff(old: &[String], new: &[String]) -> Vec<String> {
+    // common[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+    let mut diff: Vec<String> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            diff.push(format!("-{}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+{}", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// The .rem-files to format: `name` (a path, or a file in `dir`, with
+/// or without ".rem"), or all .rem-files in `dir`
+pub(crate) fn get_rem_files(dir: &str, name: Option<&str>) -> Result<Vec<PathBuf>, String> {
+    if let Some(name) = name {
+        let candidates = [
+            PathBuf::from(name),
+            Path::new(dir).join(name),
+            Path::new(dir).join(format!("{name}.rem")),
+        ];
+        return match candidates.into_iter().find(|path| path.is_file()) {
+            Some(path) => Ok(vec![path]),
+            None => Err(format!("Cannot find `{name}` (nor `{name}.rem`) in {dir}.")),
+        };
+    }
+    let entries = fs::read_dir(dir)
+        .map_err(|_| format!("Cannot read the folder `{dir}`. Try `rremind config`?"))?;
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rem"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Format the .rem-files `files`: show what changes, and (unless
+/// `check_only`) rewrite each file after asking (or right away if `yes`).
+/// Returns the number of files that were not formatted.
+pub(crate) fn format_files(
+    files: &[PathBuf],
+    date_format: &str,
+    check_only: bool,
+    yes: bool,
+) -> usize {
+    let mut unformatted = 0;
+    for file in files {
+        let name = file.to_string_lossy().to_string();
+        let Ok(contents) = fs::read_to_string(file) else {
+            eprintln!("Cannot read `{name}`.");
+            continue;
+        };
+        let old: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
+        let new = format_lines(&contents, date_format);
+        if old == new {
+            continue;
+        }
+        unformatted += 1;
+
+        println!("--- {name}\n+++ {name} (rremind fmt)");
+        for line in get_diff(&old, &new) {
+            println!("{line}");
+        }
+        let specs: Vec<_> = old
+            .iter()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .map(|line| parse_line(line))
+            .collect();
+        let unreadable = specs.iter().filter(|spec| spec.is_err()).count();
+        if unreadable > 0 {
+            println!(
+                "({unreadable} line(s) cannot be read and stay as they are; see `rremind check`)"
+            );
+        }
+        let with_ignored = specs
+            .iter()
+            .flatten()
+            .filter(|spec| !spec.ignored.is_empty())
+            .count();
+        if with_ignored > 0 {
+            println!(
+                "({with_ignored} line(s) have words rremind ignores, like `Uhr` or a room number, and stay as they are)"
+            );
+        }
+        println!();
+
+        if !check_only && (yes || confirm(&format!("Rewrite {name}? (y/n)"), false)) {
+            write_lines_to_file(&name, &new);
+            println!("{name} rewritten.");
+            unformatted -= 1;
+        }
+    }
+    unformatted
+}
+
+#[cfg(test)]
+mod test_normalize {
+    use super::{format_lines, get_diff};
+
+    #[test]
+    fn normalize_file() {
+        let contents = "# color=red\n# warn=3\n2025 may 17 AT 10:00 MSG dentist\n# standup\nmon-fri at 9:15, standup\nnov 6 REM birthday\n\n# holidays\n1.8.2025, vacation\nsomeday, x\neaster+1, Ostermontag";
+        assert_eq!(
+            format_lines(contents, "%d.%m.%Y"),
+            vec![
+                "# color=red",
+                "# warn=3",
+                "# standup",
+                "Mon-Fri 09:15, standup",
+                "Nov 6, birthday",
+                "17.05.2025 10:00, dentist",
+                "",
+                "# holidays",
+                "easter+1, Ostermontag",
+                "01.08.2025, vacation",
+                "someday, x",
+            ]
+        );
+        // words that rremind ignores are kept
+        let contents = "2025 may 17 AT 10:00 Raum 122 MSG dentist\nmon 9:00 Uhr, standup";
+        assert_eq!(
+            format_lines(contents, "%d.%m.%Y"),
+            vec![
+                "mon 9:00 Uhr, standup",
+                "2025 may 17 AT 10:00 Raum 122 MSG dentist"
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_diff() {
+        let lines = |s: &str| s.lines().map(|l| l.to_string()).collect::<Vec<String>>();
+        assert_eq!(
+            get_diff(&lines("a\nb\nc"), &lines("a\nc\nd")),
+            vec!["-b", "+d"]
+        );
+        assert!(get_diff(&lines("a\nb"), &lines("a\nb")).is_empty());
+    }
+}
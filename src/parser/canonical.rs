@@ -0,0 +1,319 @@
+// Writing a `LineSpec` back as a line of a .rem-file, in one canonical
+// form: the date, the time (10:00 or 10:00-11:30), then DURATION, WARN,
+// the modifiers, the zone and the ID, and the description after a comma.
+//
+//   2025 may 17 AT 10.00 DURATION 1 MSG dentist  =>  17.05.2025 10:00 DURATION 1, dentist
+//   mon-wed,fri at 9:15 skip-holiday, standup     =>  Mon-Wed,Fri 09:15 SKIP-HOLIDAY, standup
+//
+// Reading the canonical line gives the same `LineSpec` again.
+
+use chrono::{NaiveDate, TimeDelta, Weekday};
+
+use super::{Anchor, DateSpec, LineSpec, Shift, parse_line};
+
+impl LineSpec {
+    /// The line in canonical form; full dates are written in
+    /// `date_format` (which must be one rremind can read). The words in
+    /// `ignored` are not written.
+    pub(crate) fn to_line(&self, date_format: &str) -> String {
+        let mut words = vec![self.get_date_text(date_format)];
+        if matches!(self.date, DateSpec::Anniversary(_)) {
+            words.push("YEARLY".to_string());
+        }
+        match (self.start, self.stop) {
+            (Some(start), Some(stop)) => words.push(format!(
+                "{}-{}",
+                start.format("%H:%M"),
+                stop.format("%H:%M")
+            )),
+            (Some(start), None) => words.push(start.format("%H:%M").to_string()),
+            _ => {}
+        }
+        if let Some(duration) = self.duration {
+            words.push(format!("DURATION {}", get_duration_text(duration)));
+        }
+        if let Some(days) = self.warn {
+            words.push(format!("WARN {days}"));
+        }
+
+        let modifiers = &self.modifiers;
+        let iso = |datum: &NaiveDate| datum.format("%Y-%m-%d").to_string();
+        if modifiers.skip_weekend {
+            words.push("SKIP-WEEKEND".to_string());
+        }
+        if modifiers.skip_holiday {
+            words.push("SKIP-HOLIDAY".to_string());
+        }
+        for (name, shift) in [
+            ("IF-WEEKEND", modifiers.if_weekend),
+            ("IF-HOLIDAY", modifiers.if_holiday),
+        ] {
+            match shift {
+                Some(Shift::Before) => words.push(format!("{name} BEFORE")),
+                Some(Shift::After) => words.push(format!("{name} AFTER")),
+                None => {}
+            }
+        }
+        if let Some(from) = &modifiers.from {
+            words.push(format!("FROM {}", iso(from)));
+        }
+        if let Some(until) = &modifiers.until {
+            words.push(format!("UNTIL {}", iso(until)));
+        }
+        if !modifiers.except.is_empty() {
+            let dates: Vec<String> = modifiers.except.iter().map(iso).collect();
+            words.push(format!("EXCEPT {}", dates.join(",")));
+        }
+        if let Some(zone) = self.zone {
+            words.push(zone.name().to_string());
+        }
+        if let Some(id) = &self.id {
+            words.push(format!("ID:{id}"));
+        }
+
+        match &self.description {
+            Some(description) => format!("{}, {description}", words.join(" ")),
+            None => words.join(" "),
+        }
+    }
+
+    /// The line in canonical form, but with the words that rremind
+    /// ignores ("Uhr", room numbers) after the other items, so that they
+    /// are not lost; Err if they would be read differently there
+    pub(crate) fn to_line_with_ignored(&self, date_format: &str) -> Result<String, String> {
+        if self.ignored.is_empty() {
+            return Ok(self.to_line(date_format));
+        }
+        let ignored = self.ignored.join(" ");
+        let spec_text = LineSpec {
+            description: None,
+            ..self.clone()
+        }
+        .to_line(date_format);
+        let line = match &self.description {
+            Some(description) => format!("{spec_text} {ignored}, {description}"),
+            None => format!("{spec_text} {ignored}"),
+        };
+        match parse_line(&line) {
+            Ok(spec) if spec == *self => Ok(line),
+            _ => Err(format!(
+                "`{ignored}` are words rremind ignores, and they would get lost"
+            )),
+        }
+    }
+
+    /// The date of the line: "17.05.2025", "Nov 6", "Mon-Fri", "easter+49"
+    pub(crate) fn get_date_text(&self, date_format: &str) -> String {
+        match &self.date {
+            DateSpec::Full(datum) | DateSpec::Anniversary(datum) => {
+                datum.format(date_format).to_string()
+            }
+            DateSpec::Yearly { month, day } => match NaiveDate::from_ymd_opt(2024, *month, *day) {
+                Some(datum) => datum.format("%b %-d").to_string(),
+                None => format!("{month} {day}"), // cannot be parsed
+            },
+            DateSpec::Weekly(weekdays) => get_weekdays_text(weekdays),
+            DateSpec::Anchor(anchor, offset) => {
+                let name = match anchor {
+                    Anchor::Easter => "easter".to_string(),
+                    Anchor::Advent(n) => format!("advent{n}"),
+                };
+                match offset {
+                    0 => name,
+                    _ => format!("{name}{offset:+}"),
+                }
+            }
+        }
+    }
+}
+
+// [Mon, Tue, Wed, Fri] => "Mon-Wed,Fri", all seven days => "daily"
+fn get_weekdays_text(weekdays: &[Weekday]) -> String {
+    let mut days: Vec<u32> = weekdays.iter().map(|d| d.num_days_from_monday()).collect();
+    days.sort();
+    days.dedup();
+    if days.len() == 7 {
+        return "daily".to_string();
+    }
+    let name = |n: u32| Weekday::try_from(n as u8).map_or(String::new(), |d| d.to_string());
+    // Runs of consecutive days, three or more of them as a range
+    let mut runs: Vec<(u32, u32)> = vec![];
+    for day in days {
+        match runs.last_mut() {
+            Some((_, last)) if *last + 1 == day => *last = day,
+            _ => runs.push((day, day)),
+        }
+    }
+    let mut items: Vec<String> = vec![];
+    for (first, last) in runs {
+        match last - first {
+            0 => items.push(name(first)),
+            1 => items.extend([name(first), name(last)]),
+            _ => items.push(format!("{}-{}", name(first), name(last))),
+        }
+    }
+    items.join(",")
+}
+
+// DURATION counts minutes, but 8 or less are hours: 90 => "90",
+// 2 hours => "2", 5 minutes => "0.0833"
+fn get_duration_text(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes();
+    match minutes {
+        m if m % 60 == 0 && m / 60 <= 8 => (m / 60).to_string(),
+        m if m > 8 => m.to_string(),
+        m => format!("{:.4}", m as f64 / 60.0),
+    }
+}
+
+#[cfg(test)]
+mod test_canonical {
+    use chrono::{NaiveDate, NaiveTime, TimeDelta, Weekday};
+    use chrono_tz::Tz;
+    use proptest::prelude::*;
+
+    use crate::parser::{Anchor, DateSpec, LineSpec, Modifiers, Shift, parse_line};
+
+    #[test]
+    fn canonical_lines() {
+        let canonical = |line: &str| parse_line(line).unwrap().to_line("%d.%m.%Y");
+        assert_eq!(
+            canonical("2025 may 17 AT 10.00 DURATION 1 MSG dentist"),
+            "17.05.2025 10:00 DURATION 1, dentist"
+        );
+        assert_eq!(
+            canonical("mon-wed,fri at 9:15 skip-holiday, standup"),
+            "Mon-Wed,Fri 09:15 SKIP-HOLIDAY, standup"
+        );
+        assert_eq!(canonical("Sat-Mon REM weekend"), "Mon,Sat,Sun, weekend");
+        assert_eq!(
+            canonical("1980 nov 6 yearly WARN 7, Heiko turns {age}"),
+            "06.11.1980 YEARLY WARN 7, Heiko turns {age}"
+        );
+        assert_eq!(canonical("nov 6"), "Nov 6");
+        assert_eq!(
+            canonical("2025-05-17 id:Dentist-2 at 10:00, dentist"),
+            "17.05.2025 10:00 ID:Dentist-2, dentist"
+        );
+        assert_eq!(
+            canonical("advent4-1 3pm-4pm, x"),
+            "advent4-1 15:00-16:00, x"
+        );
+
+        // Words that rremind ignores are kept, after the other items
+        let keeping = |line: &str| parse_line(line).unwrap().to_line_with_ignored("%d.%m.%Y");
+        assert_eq!(
+            keeping("2026-10-20 10:00 Raum 122, meeting"),
+            Ok("20.10.2026 10:00 Raum 122, meeting".to_string())
+        );
+        assert_eq!(
+            keeping("mon 10:00 Uhr ID:x, standup"),
+            Ok("Mon 10:00 ID:x Uhr, standup".to_string())
+        );
+        assert_eq!(keeping("Mon 9:00, x"), Ok("Mon 09:00, x".to_string()));
+    }
+
+    fn any_date() -> impl Strategy<Value = NaiveDate> {
+        (1900i32..2100, 1u32..=12, 1u32..=28)
+            .prop_map(|(y, m, d)| NaiveDate::from_ymd_opt(y, m, d).unwrap())
+    }
+
+    fn any_date_spec() -> impl Strategy<Value = DateSpec> {
+        prop_oneof![
+            any_date().prop_map(DateSpec::Full),
+            any_date().prop_map(DateSpec::Anniversary),
+            (1u32..=12, 1u32..=29).prop_map(|(month, day)| DateSpec::Yearly { month, day }),
+            prop::collection::btree_set(0u8..7, 1..=7).prop_map(|days| DateSpec::Weekly(
+                days.into_iter()
+                    .map(|d| Weekday::try_from(d).unwrap())
+                    .collect()
+            )),
+            (0u32..=4, -100i64..100).prop_map(|(n, offset)| match n {
+                0 => DateSpec::Anchor(Anchor::Easter, offset),
+                n => DateSpec::Anchor(Anchor::Advent(n), offset),
+            }),
+        ]
+    }
+
+    fn any_shift() -> impl Strategy<Value = Option<Shift>> {
+        prop_oneof![
+            Just(None),
+            Just(Some(Shift::Before)),
+            Just(Some(Shift::After))
+        ]
+    }
+
+    fn any_modifiers() -> impl Strategy<Value = Modifiers> {
+        (
+            any::<bool>(),
+            any::<bool>(),
+            any_shift(),
+            any_shift(),
+            prop::option::of(any_date()),
+            prop::option::of(any_date()),
+            prop::collection::vec(any_date(), 0..3),
+        )
+            .prop_map(
+                |(skip_weekend, skip_holiday, if_weekend, if_holiday, from, until, except)| {
+                    Modifiers {
+                        skip_weekend,
+                        skip_holiday,
+                        if_weekend,
+                        if_holiday,
+                        from,
+                        until,
+                        except,
+                    }
+                },
+            )
+    }
+
+    fn any_line_spec() -> impl Strategy<Value = LineSpec> {
+        let time =
+            (0u32..24, 0u32..60).prop_map(|(h, m)| NaiveTime::from_hms_opt(h, m, 0).unwrap());
+        (
+            any_date_spec(),
+            prop::option::of((time.clone(), prop::option::of(time))),
+            prop::option::of(0i64..600),
+            prop::option::of(0u64..30),
+            any_modifiers(),
+            prop::option::of(prop_oneof![
+                Just(Tz::America__New_York),
+                Just(Tz::Asia__Tokyo),
+                Just(Tz::UTC)
+            ]),
+            prop::option::of("[a-zA-Z0-9_-]{1,12}"),
+            prop::option::of("[a-zA-Z0-9#'{}ß ,.-]{0,30}"),
+        )
+            .prop_map(
+                |(date, times, minutes, warn, modifiers, zone, id, description)| {
+                    let (start, stop) = times.unzip();
+                    LineSpec {
+                        date,
+                        start,
+                        stop: stop.flatten(),
+                        // DURATION needs a start time
+                        duration: minutes
+                            .filter(|_| start.is_some())
+                            .and_then(TimeDelta::try_minutes),
+                        warn,
+                        modifiers,
+                        zone,
+                        id,
+                        description,
+                        ignored: vec![],
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_round_trip(spec in any_line_spec()) {
+            for date_format in ["%d.%m.%Y", "%Y-%m-%d"] {
+                let line = spec.to_line(date_format);
+                prop_assert_eq!(parse_line(&line), Ok(spec.clone()), "{}", line);
+            }
+        }
+    }
+}
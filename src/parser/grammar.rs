@@ -0,0 +1,612 @@
+// The grammar of a line in a .rem-file. Keywords and names are
+// case-insensitive, words are separated by blanks:
+//
+//   line      = spec [ separator description ]
+//   separator = ", " | " REM " | " MSG "          (whichever comes first)
+//   spec      = date { item }
+//   date      = full-date [ "YEARLY" ]           2025-05-17, 17.5.2025, 2025 may 17
+//             | month day                        may 17 (every year)
+//             | weekdays                         Mon, Mon-Fri, Mon,Wed,Fri, daily
+//             | anchor                           easter, easter+49, advent1-1
+//   item      = "AT"                             (optional, reads well)
+//             | time [ "-" time ]                10:00, 10.00-11.30, 3pm, 9-11am, noon
+//             | "DURATION" number                minutes; hours if 8 or less (1.5: 90 minutes)
+//             | "WARN" days
+//             | "YEARLY"
+//             | "SKIP-WEEKEND" | "SKIP-HOLIDAY"
+//             | ( "IF-WEEKEND" | "IF-HOLIDAY" ) ( "BEFORE" | "AFTER" )
+//             | "FROM" date | "UNTIL" date | "EXCEPT" date { "," date }
+//             | zone                             America/New_York, UTC
+//             | "ID:" name                       ID:dentist (letters, digits, - and _)
+//             | word                             anything else is ignored
+//
+// Without a separator, the whole line is the spec, and there is no
+// description. FROM, UNTIL and EXCEPT are keywords only with a date after
+// them ("from home" are two words). A line is read once, into a
+// `LineSpec`; what cannot be read is a `ParseError` that knows where in
+// the line it is. For listings, a line whose date can be read is taken
+// without the items that cannot (`parse_line_leniently`).
+
+use std::fmt;
+
+use chrono::{NaiveDate, NaiveTime, TimeDelta, Weekday};
+use chrono_tz::Tz;
+
+use super::{
+    Anchor, Modifiers, Shift, as_date, get_month_as_no, is_modifier, parse_anchor,
+    parse_range_start, parse_time, parse_weekdays,
+};
+use crate::timezone::parse_zone;
+
+const DATE_SUGGESTION: &str = "start the line with a date: 2025 may 17, 17.5.2025, 2025-05-17, may 17, Mon, Mon-Fri, daily, easter+1";
+const TIME_SUGGESTION: &str = "14:00, 14.00, 2pm or 2:30pm; a period of time as 14:00-15:30";
+const DURATION_SUGGESTION: &str = "give a start time and the minutes: at 14:00 DURATION 90";
+
+// The part of the line a problem is in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Part {
+    Date,
+    Time,
+    Duration,
+    Modifier,
+    Description,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Part::Date => "date",
+            Part::Time => "time",
+            Part::Duration => "duration",
+            Part::Modifier => "modifier",
+            Part::Description => "description",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ParseError {
+    pub(crate) column: usize, // 1-based, in characters
+    pub(crate) part: Part,
+    pub(crate) message: String,
+    pub(crate) suggestion: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (column {})",
+            self.part, self.message, self.column
+        )
+    }
+}
+
+fn error(column: usize, part: Part, message: String, suggestion: &'static str) -> ParseError {
+    ParseError {
+        column,
+        part,
+        message,
+        suggestion,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DateSpec {
+    Full(NaiveDate),
+    Anniversary(NaiveDate), // 1980 nov 6 YEARLY: every year since 1980
+    Yearly { month: u32, day: u32 },
+    Weekly(Vec<Weekday>),
+    Anchor(Anchor, i64), // easter+49
+}
+
+/// One line of a .rem-file, as the grammar reads it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LineSpec {
+    pub(crate) date: DateSpec,
+    pub(crate) start: Option<NaiveTime>,
+    pub(crate) stop: Option<NaiveTime>,     // 10:00-11:00 ...
+    pub(crate) duration: Option<TimeDelta>, // ... or 10:00 DURATION 60
+    pub(crate) warn: Option<u64>,
+    pub(crate) modifiers: Modifiers,
+    pub(crate) zone: Option<Tz>,
+    pub(crate) id: Option<String>, // ID:dentist, see ids/mod.rs
+    pub(crate) description: Option<String>,
+    pub(crate) ignored: Vec<String>, // words of the spec that are ignored: "Uhr", room numbers, a second time
+}
+
+impl LineSpec {
+    /// The end of the appointment: as written, or start and DURATION
+    pub(crate) fn get_stop(&self) -> Option<NaiveTime> {
+        self.stop
+            .or_else(|| Some(self.start?.overflowing_add_signed(self.duration?).0))
+    }
+}
+
+/// A word of the spec and where it starts in the line
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub(crate) text: String,
+    pub(crate) column: usize, // 1-based, in characters
+}
+
+/// The line split at the first separator: the spec and the description
+/// (None if there is no separator)
+pub(crate) fn split_line(line: &str) -> (&str, Option<&str>) {
+    // ASCII lower case keeps the byte positions of `line`
+    let small = line.to_ascii_lowercase();
+    [", ", " rem ", " msg "]
+        .iter()
+        .filter_map(|separator| small.find(separator).map(|i| (i, separator.len())))
+        .min()
+        .map_or((line, None), |(i, len)| {
+            (&line[..i], Some(&line[i + len..]))
+        })
+}
+
+/// The words of the spec of `line`; "10:00 - 11:00" is one word, as is "3 pm"
+pub(crate) fn tokenize(line: &str) -> Vec<Token> {
+    let (spec, _) = split_line(line);
+    let mut words: Vec<Token> = vec![];
+    let mut in_word = false;
+    for (i, c) in spec.chars().enumerate() {
+        if c.is_whitespace() {
+            in_word = false;
+            continue;
+        }
+        match (in_word, words.last_mut()) {
+            (true, Some(word)) => word.text.push(c),
+            _ => words.push(Token {
+                text: c.to_string(),
+                column: i + 1,
+            }),
+        }
+        in_word = true;
+    }
+
+    let mut tokens: Vec<Token> = vec![];
+    let mut joining = false; // the last word was a lone "-"
+    for word in words {
+        let small = word.text.to_lowercase();
+        let is_suffix = ["am", "pm", "a.m.", "p.m."].contains(&small.as_str());
+        let joins = joining || small == "-" || (is_suffix && takes_suffix(&tokens));
+        match tokens.last_mut() {
+            Some(last) if joins => {
+                last.text.push_str(&word.text);
+                joining = small == "-";
+            }
+            _ => tokens.push(word),
+        }
+    }
+    tokens
+}
+
+// Can "am" or "pm" follow the last of `tokens`? Only an hour or a clock
+// time can (3, 10:30, 9-11), not the day of a date ("oct 19 am Bahnhof")
+fn takes_suffix(tokens: &[Token]) -> bool {
+    let Some(last) = tokens.last() else {
+        return false;
+    };
+    if !last.text.starts_with(|c: char| c.is_ascii_digit())
+        || !last
+            .text
+            .chars()
+            .all(|c| c.is_ascii_digit() || ":.-".contains(c))
+    {
+        return false;
+    }
+    match last.text.parse::<u32>() {
+        Ok(hour) => {
+            hour <= 12
+                && (tokens.len() < 2 || get_month_as_no(&tokens[tokens.len() - 2].text).is_none())
+        }
+        Err(_) => true,
+    }
+}
+
+/// Does the word stand for a time or a period of time? Dates (easter-2,
+/// 2025-05-17), weekdays (mon-fri), zones and modifiers do not, nor do
+/// room numbers (B-12, 1-3). A word that reads like a clock time but is
+/// none (25:00, 9am-13pm) is one, so that it can be reported.
+pub(crate) fn is_time_word(word: &str) -> bool {
+    !(parse_anchor(word).is_some()
+        || as_date(word).is_some()
+        || parse_weekdays(word).is_some()
+        || parse_zone(word).is_some()
+        || is_modifier(word)
+        || word.to_lowercase().starts_with("id:"))
+        && (reads_as_time(word) || looks_like_time(word))
+}
+
+// 10:00, 3pm, 10.00-11.30, 9-11am
+fn reads_as_time(word: &str) -> bool {
+    match word.split_once('-') {
+        Some((from, to)) => parse_range_start(from, to).is_some() && parse_time(to).is_some(),
+        None => parse_time(word).is_some(),
+    }
+}
+
+// Digits with a colon or "am"/"pm", and nothing but clock characters
+fn looks_like_time(word: &str) -> bool {
+    let small = word.to_lowercase();
+    small.chars().any(|c| c.is_ascii_digit())
+        && (small.contains(':') || small.contains("am") || small.contains("pm"))
+        && small
+            .chars()
+            .all(|c| c.is_ascii_digit() || ":.-apm".contains(c))
+}
+
+// A date, or what is meant to be one: 2025-05-17, 30.2.2025, and lists
+// of them (2025-05-08,2025-05-15); not a time like 9.30
+fn is_date_word(word: &str) -> bool {
+    let first = word.split(',').next().unwrap_or_default();
+    as_date(first).is_some()
+        || (first.starts_with(|c: char| c.is_ascii_digit())
+            && (first.contains('.') || first.contains('-'))
+            && first
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+            && parse_time(first).is_none())
+}
+
+/// Can `name` follow "ID:"?
+fn is_id_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Read `line` according to the grammar above
+pub(crate) fn parse_line(line: &str) -> Result<LineSpec, ParseError> {
+    match read_line(line)? {
+        (spec, None) => Ok(spec),
+        (_, Some(e)) => Err(e),
+    }
+}
+
+/// Read `line` like `parse_line`, but only the date has to be right:
+/// items that cannot be read (`AT 25:00`, `DURATION 1h`) are left out,
+/// so that the appointment is still listed. `check` reports them.
+pub(crate) fn parse_line_leniently(line: &str) -> Result<LineSpec, ParseError> {
+    read_line(line).map(|(spec, _)| spec)
+}
+
+// The spec of `line` without the items that cannot be read, and the
+// first problem with these items; Err if the date cannot be read
+fn read_line(line: &str) -> Result<(LineSpec, Option<ParseError>), ParseError> {
+    let tokens = tokenize(line);
+    let (date, used) = parse_date_spec(&tokens)?;
+    let mut spec = LineSpec {
+        date,
+        start: None,
+        stop: None,
+        duration: None,
+        warn: None,
+        modifiers: Modifiers::default(),
+        zone: None,
+        id: None,
+        description: split_line(line).1.map(str::to_string),
+        ignored: vec![],
+    };
+    let mut problem: Option<ParseError> = None;
+
+    let items = &tokens[used..];
+    let mut i = 0;
+    while i < items.len() {
+        match read_item(items, i, &mut spec) {
+            Ok(words) => i += words,
+            Err(e) => {
+                problem = problem.or(Some(e));
+                i += 1;
+            }
+        }
+    }
+
+    if spec.duration.is_some() && spec.start.is_none() {
+        let column = items
+            .iter()
+            .find(|token| token.text.eq_ignore_ascii_case("duration"))
+            .map_or(1, |token| token.column);
+        spec.duration = None;
+        problem = problem.or(Some(error(
+            column,
+            Part::Duration,
+            "there is no start time for the duration".to_string(),
+            DURATION_SUGGESTION,
+        )));
+    }
+    Ok((spec, problem))
+}
+
+// Read the item at `items[i]` into `spec`; the number of words it takes
+// up (a keyword and its value: 2)
+fn read_item(items: &[Token], i: usize, spec: &mut LineSpec) -> Result<usize, ParseError> {
+    let token = &items[i];
+    let small = token.text.to_lowercase();
+    // The word after a keyword (DURATION 90, FROM 2025-01-01)
+    let argument = |part: Part, suggestion: &'static str| match items.get(i + 1) {
+        Some(next) => Ok(next),
+        None => Err(error(
+            token.column,
+            part,
+            format!("`{}` needs a value", token.text),
+            suggestion,
+        )),
+    };
+    match small.as_str() {
+        "at" => {}
+        "yearly" => {
+            if let DateSpec::Full(origin) = spec.date {
+                spec.date = DateSpec::Anniversary(origin);
+            }
+        }
+        "duration" => {
+            let value = argument(Part::Duration, DURATION_SUGGESTION)?;
+            spec.duration = Some(parse_duration(&value.text).ok_or(error(
+                value.column,
+                Part::Duration,
+                format!("cannot read the duration `{}`", value.text),
+                DURATION_SUGGESTION,
+            ))?);
+            return Ok(2);
+        }
+        "warn" => {
+            let suggestion = "the number of days: nov 6 WARN 7, birthday";
+            let value = argument(Part::Modifier, suggestion)?;
+            spec.warn = Some(value.text.parse::<u64>().map_err(|_| {
+                error(
+                    value.column,
+                    Part::Modifier,
+                    format!("cannot read the days `{}` after WARN", value.text),
+                    suggestion,
+                )
+            })?);
+            return Ok(2);
+        }
+        "skip-weekend" => spec.modifiers.skip_weekend = true,
+        "skip-holiday" => spec.modifiers.skip_holiday = true,
+        "if-weekend" | "if-holiday" => {
+            let suggestion = "IF-WEEKEND BEFORE, IF-HOLIDAY AFTER";
+            let value = argument(Part::Modifier, suggestion)?;
+            let shift = match value.text.to_lowercase().as_str() {
+                "before" => Shift::Before,
+                "after" => Shift::After,
+                _ => {
+                    return Err(error(
+                        value.column,
+                        Part::Modifier,
+                        format!("`{}` needs BEFORE or AFTER", token.text),
+                        suggestion,
+                    ));
+                }
+            };
+            match small.as_str() {
+                "if-weekend" => spec.modifiers.if_weekend = Some(shift),
+                _ => spec.modifiers.if_holiday = Some(shift),
+            }
+            return Ok(2);
+        }
+        // Keywords only if a date follows ("from home", "until 2pm" are words)
+        "from" | "until" | "except"
+            if items
+                .get(i + 1)
+                .is_some_and(|next| is_date_word(&next.text)) =>
+        {
+            let suggestion = "FROM 2025-05-01, UNTIL 31.5.2025, EXCEPT 2025-05-08,2025-05-15";
+            let value = &items[i + 1];
+            let mut dates: Vec<NaiveDate> = vec![];
+            for text in value.text.split(',') {
+                dates.push(as_date(text).ok_or(error(
+                    value.column,
+                    Part::Modifier,
+                    format!("cannot read the date `{text}` after {}", token.text),
+                    suggestion,
+                ))?);
+            }
+            match small.as_str() {
+                "from" => spec.modifiers.from = dates.first().copied(),
+                "until" => spec.modifiers.until = dates.first().copied(),
+                _ => spec.modifiers.except = dates,
+            }
+            return Ok(2);
+        }
+        _ if parse_zone(&token.text).is_some() => spec.zone = parse_zone(&token.text),
+        _ if small.starts_with("id:") => {
+            let name = &token.text[3..];
+            if !is_id_name(name) {
+                return Err(error(
+                    token.column,
+                    Part::Modifier,
+                    format!("cannot read the id `{}`", token.text),
+                    "ID: and a name of letters, digits, - and _: ID:dentist-2025",
+                ));
+            }
+            spec.id = Some(name.to_string());
+        }
+        _ if is_time_word(&small) => {
+            let (start, stop) = match small.split_once('-') {
+                Some((from, to)) => (parse_range_start(from, to), parse_time(to)),
+                None => (parse_time(&small), None),
+            };
+            if start.is_none() || (small.contains('-') && stop.is_none()) {
+                return Err(error(
+                    token.column,
+                    Part::Time,
+                    format!("cannot read the time `{}`", token.text),
+                    TIME_SUGGESTION,
+                ));
+            }
+            // The first time is the time of the appointment
+            match spec.start {
+                None => (spec.start, spec.stop) = (start, stop),
+                Some(_) => spec.ignored.push(token.text.clone()),
+            }
+        }
+        _ => spec.ignored.push(token.text.clone()), // "Uhr", room numbers, ...
+    }
+    Ok(1)
+}
+
+// The date at the start of the spec, and how many words it takes up
+fn parse_date_spec(tokens: &[Token]) -> Result<(DateSpec, usize), ParseError> {
+    let Some(first) = tokens.first() else {
+        return Err(error(
+            1,
+            Part::Date,
+            "there is no date".to_string(),
+            DATE_SUGGESTION,
+        ));
+    };
+    let word = |i: usize| tokens.get(i).map(|t| t.text.as_str()).unwrap_or_default();
+    let no_such_date = |text: String| {
+        error(
+            first.column,
+            Part::Date,
+            format!("there is no such date: `{text}`"),
+            "check the day and the month",
+        )
+    };
+
+    // 17.5.2025, 2025-05-17
+    if let Some(datum) = as_date(&first.text) {
+        return Ok((DateSpec::Full(datum), 1));
+    }
+    if looks_like_date(&first.text) {
+        return Err(no_such_date(first.text.clone()));
+    }
+    // 2025 may 17
+    if let Ok(year) = first.text.parse::<i32>()
+        && let Some(month) = get_month_as_no(word(1))
+    {
+        return match word(2)
+            .parse::<u32>()
+            .ok()
+            .and_then(|day| NaiveDate::from_ymd_opt(year, month as u32, day))
+        {
+            Some(datum) => Ok((DateSpec::Full(datum), 3)),
+            None => Err(no_such_date(format!("{} {} {}", word(0), word(1), word(2)))),
+        };
+    }
+    // Sat-Mon is the same as Mon,Sat,Sun
+    if let Some(mut weekdays) = parse_weekdays(&first.text) {
+        weekdays.sort_by_key(|day| day.num_days_from_monday());
+        weekdays.dedup();
+        return Ok((DateSpec::Weekly(weekdays), 1));
+    }
+    // may 17; 2024 is a leap year, so feb 29 is fine
+    if let Some(month) = get_month_as_no(&first.text) {
+        return match word(1).parse::<u32>() {
+            Ok(day) if NaiveDate::from_ymd_opt(2024, month as u32, day).is_some() => Ok((
+                DateSpec::Yearly {
+                    month: month as u32,
+                    day,
+                },
+                2,
+            )),
+            _ => Err(no_such_date(format!("{} {}", word(0), word(1)))),
+        };
+    }
+    if let Some((anchor, offset)) = parse_anchor(&first.text) {
+        return Ok((DateSpec::Anchor(anchor, offset), 1));
+    }
+    Err(error(
+        first.column,
+        Part::Date,
+        format!("cannot read the date starting with `{}`", first.text),
+        DATE_SUGGESTION,
+    ))
+}
+
+// Looks like 30.2.2025 or 2025-02-30
+fn looks_like_date(word: &str) -> bool {
+    word.chars().filter(|c| *c == '.' || *c == '-').count() == 2
+        && word
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+}
+
+// "90" => 90 minutes, "2" => 2 hours, "1.5" (or "1,5") => 90 minutes
+fn parse_duration(word: &str) -> Option<TimeDelta> {
+    let number = word.replace(',', ".");
+    if number.contains('.') {
+        let hours = number.parse::<f64>().ok()?;
+        return TimeDelta::try_minutes((hours * 60.0).round() as i64);
+    }
+    match number.parse::<i64>().ok()? {
+        minutes if minutes > 8 => TimeDelta::try_minutes(minutes),
+        hours => TimeDelta::try_hours(hours),
+    }
+}
+
+#[cfg(test)]
+mod test_grammar {
+    use chrono::{NaiveDate, NaiveTime, TimeDelta, Weekday};
+
+    use super::{DateSpec, Part, parse_line, tokenize};
+
+    #[test]
+    fn grammar_tokens() {
+        let words: Vec<(String, usize)> = tokenize("Mon AT 10:00 - 11:00 3 pm, 9:00 x")
+            .into_iter()
+            .map(|t| (t.text, t.column))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("Mon".to_string(), 1),
+                ("AT".to_string(), 5),
+                ("10:00-11:00".to_string(), 8),
+                ("3pm".to_string(), 22)
+            ]
+        );
+    }
+
+    #[test]
+    fn grammar_lines() {
+        let t = |h: u32, m: u32| NaiveTime::from_hms_opt(h, m, 0);
+        // The line from the German comment that used to break
+        let spec = parse_line("3.12.2025 15:00, Besprechung").unwrap();
+        assert_eq!(
+            spec.date,
+            DateSpec::Full(NaiveDate::from_ymd_opt(2025, 12, 3).unwrap())
+        );
+        assert_eq!(spec.start, t(15, 0));
+        assert_eq!(spec.description.as_deref(), Some("Besprechung"));
+
+        let spec = parse_line("Mon,Fri at 10:00 DURATION 1.5 WARN 2 msg x, y").unwrap();
+        assert_eq!(
+            spec.date,
+            DateSpec::Weekly(vec![Weekday::Mon, Weekday::Fri])
+        );
+        assert_eq!(spec.duration, TimeDelta::try_minutes(90));
+        assert_eq!(spec.get_stop(), t(11, 30));
+        assert_eq!(spec.warn, Some(2));
+        assert_eq!(spec.description.as_deref(), Some("x, y"));
+
+        let spec = parse_line("1980 nov 6 YEARLY").unwrap();
+        assert!(matches!(spec.date, DateSpec::Anniversary(_)));
+        assert!(spec.description.is_none());
+
+        // Room numbers are no times
+        let spec = parse_line("Mon AT 10:00 B-12, room").unwrap();
+        assert_eq!(spec.start, t(10, 0));
+        assert_eq!(spec.ignored, vec!["B-12"]);
+        let spec = parse_line("Mon 9-11am R1-3 1-3, rooms").unwrap();
+        assert_eq!((spec.start, spec.stop), (t(9, 0), t(11, 0)));
+
+        let fails = |line: &str| {
+            let e = parse_line(line).unwrap_err();
+            (e.column, e.part)
+        };
+        assert_eq!(fails("someday, x"), (1, Part::Date));
+        assert_eq!(fails("feb 30, x"), (1, Part::Date));
+        assert_eq!(fails("Mon 9am-13pm, x"), (5, Part::Time));
+        assert_eq!(fails("Mon AT 25:00, x"), (8, Part::Time));
+        assert_eq!(fails("Mon DURATION 90, x"), (5, Part::Duration));
+        assert_eq!(fails("Mon 10:00 UNTIL 30.2.2025, x"), (17, Part::Modifier));
+        assert_eq!(fails("Mon IF-WEEKEND, x"), (5, Part::Modifier));
+        assert_eq!(fails("Mon 10:00 ID:, x"), (11, Part::Modifier));
+    }
+}
@@ -4,16 +4,18 @@
 // use core::slice::SlicePattern;
 // use std::ops::Index;
 
-use chrono::{Datelike, Days, NaiveDate, NaiveTime, TimeDelta};
+use chrono::{Datelike, Days, NaiveDate, NaiveTime, TimeDelta, Weekday};
 
-use crate::{Appointment, color::COLOR_BLUE};
+use crate::{Appointment, color::COLOR_BLUE, holidays};
 
-// Start_Time and Stop_Time: 10:00 DURATION 20 is possible, make
-// 10:00-10:20 possible, too
-struct TimeHelper {
-    start: Option<NaiveTime>,
-    stop: Option<NaiveTime>,
-}
+mod canonical;
+mod grammar;
+mod relative;
+pub(crate) use grammar::{
+    DateSpec, LineSpec, ParseError, Part, is_time_word, parse_line, parse_line_leniently,
+    split_line, tokenize,
+};
+pub(crate) use relative::{parse_period, parse_relative_date};
 
 const MONTHS: &'static [&'static str] = &[
     "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
@@ -21,7 +23,23 @@ const MONTHS: &'static [&'static str] = &[
 
 const DAYS: &'static [&'static str] = &["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
 
-const NO_INFO: &str =
+// German names are accepted, too ("Mär", "Mrz", "Dez"; "Mo", "Montag").
+// Weekdays only as two letters or in full: otherwise "Dinner" would be
+// on Tuesdays.
+const DE_MONTHS: &[&str] = &[
+    "jan", "feb", "mär", "apr", "mai", "jun", "jul", "aug", "sep", "okt", "nov", "dez",
+];
+const DE_DAYS: &[[&str; 2]] = &[
+    ["mo", "montag"],
+    ["di", "dienstag"],
+    ["mi", "mittwoch"],
+    ["do", "donnerstag"],
+    ["fr", "freitag"],
+    ["sa", "samstag"],
+    ["so", "sonntag"],
+];
+
+pub(crate) const NO_INFO: &str =
     "Appointment contains no information (add 'msg' or 'rem' to the line to change that)";
 
 pub(crate) fn is_date(s_text: &str) -> bool {
@@ -47,226 +65,239 @@ pub(crate) fn as_date(s_text: &str) -> Option<NaiveDate> {
 /// Appointments without month indication return the next matching
 /// weekday relative to today
 ///
-/// If the text cannot be parsed, `None` is returned.
+/// If the date cannot be parsed, `None` is returned; other parts that
+/// cannot be parsed are left out (see `parse_line_leniently`).
 pub fn get_termin_from_line(s_text: &str, start_date: Option<NaiveDate>) -> Option<Appointment> {
-    if let Some(r) = get_termin_from_full_date(&s_text) {
-        return Some(r);
-    }
-    if let Some(s) = get_termin_without_month(&s_text, start_date) {
-        return Some(s);
-    }
-    if let Some(t) = get_termin_without_year(&s_text, start_date) {
-        return Some(t);
-    }
-
-    None
+    let spec = parse_line_leniently(s_text).ok()?;
+    let termin = get_termin_from_spec(&spec, start_date);
+    Some(apply_modifiers(
+        &spec,
+        termin,
+        start_date,
+        &holidays::is_holiday,
+    ))
 }
 
-fn parse_date(s_in: &str) -> Option<NaiveDate> {
-    let words: Vec<&str> = s_in.split_whitespace().collect();
-    let mut dtm: Option<NaiveDate> = None;
-    // 18.12.2025
-    if let Ok(datum) = NaiveDate::parse_from_str(words.get(0).unwrap_or(&""), "%d.%m.%Y") {
-        dtm = Some(datum);
-    } else if let Ok(datum) = NaiveDate::parse_from_str(words.get(0).unwrap_or(&""), "%Y-%m-%d") {
-        dtm = Some(datum);
-    } else {
-        if let Ok(year) = words.get(0)?.parse::<i32>() {
-            let month = get_month_as_no(words.get(1)?)?;
-            if let Ok(day) = words.get(2)?.parse::<usize>() {
-                dtm = NaiveDate::from_ymd_opt(year, month as u32, day as u32);
-            }
+// The occurrence of a line on or after `start_date` (today): full dates
+// as they are, yearly appointments in the year of `start_date`, weekly
+// ones and anchors on the next matching day.
+fn get_termin_from_spec(spec: &LineSpec, start_date: Option<NaiveDate>) -> Appointment {
+    let start = start_date.unwrap_or(chrono::offset::Local::now().date_naive());
+    let (datum, origin_year) = match &spec.date {
+        DateSpec::Full(datum) => (Some(*datum), None),
+        // Mapped to the year of `start_date`, but not to years before `origin`
+        DateSpec::Anniversary(origin) => (
+            match start.year() >= origin.year() {
+                true => NaiveDate::from_ymd_opt(start.year(), origin.month(), origin.day()),
+                false => None,
+            },
+            Some(origin.year()),
+        ),
+        DateSpec::Yearly { month, day } => {
+            (NaiveDate::from_ymd_opt(start.year(), *month, *day), None)
         }
+        DateSpec::Weekly(weekdays) => (find_next_date(weekdays, start), None),
+        DateSpec::Anchor(anchor, offset) => (get_anchor_occurrence(anchor, *offset, start), None),
+    };
+    let color = match spec.date {
+        DateSpec::Weekly(_) | DateSpec::Anchor(..) => None,
+        _ => Some(COLOR_BLUE.to_owned()),
+    };
+    Appointment {
+        appointment_date: datum,
+        appointment_is_full_date: matches!(spec.date, DateSpec::Full(_)),
+        appointment_start: spec.start,
+        appointment_stop: spec.get_stop(),
+        appointment_description: spec.description.clone().unwrap_or(NO_INFO.to_string()),
+        appointment_date_alt_text: spec.get_date_text("%Y-%m-%d"),
+        appointment_color: color,
+        appointment_file: None,
+        appointment_days_ahead: None,
+        appointment_origin_year: origin_year,
+        appointment_zone: None,
+        appointment_id: None,
     }
-    dtm
-}
-// 2024 sep 9 AT 10:00 DURATION 1 MSG Whatever
-// @todo: needs recrafting
-pub(crate) fn get_termin_from_full_date(s_in: &str) -> Option<Appointment> {
-    // let words: Vec<&str> = s_in.split_whitespace().collect();
-    if let Some(datum) = parse_date(s_in) {
-        let t: TimeHelper = extract_duration(s_in);
-        return Some(Appointment {
-            appointment_date: Some(datum),
-            appointment_is_full_date: true,
-            // appointment_start: extract_start_time(s_in),
-            // appointment_stop: extract_stop_time(s_in),
-            appointment_start: t.start,
-            appointment_stop: t.stop,
-            appointment_description: extract_description(s_in),
-            appointment_date_alt_text: extract_datum_text(s_in),
-            appointment_color: Some(COLOR_BLUE.to_owned()),
-        });
-    }
-    None
 }
 
-// @todo: move to utils,
-// include in tests (!)
-fn split_once_ignore_case(s_in: &str, splitter: &str) -> Option<(String, String)> {
-    let s_in_small = s_in.to_lowercase();
-    let splitter_small = splitter.to_lowercase();
+// Where an occurrence on a weekend or holiday goes: IF-WEEKEND BEFORE
+// moves it to the working day before, IF-WEEKEND AFTER to the one after.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Shift {
+    Before,
+    After,
+}
 
-    if let Some(start) = s_in_small.find(&splitter_small) {
-        let c1 = s_in[0..start].to_string();
-        let c2 = s_in[start + splitter.len() + 1..].to_string();
-        return Some((c1, c2));
-    }
-    None
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Modifiers {
+    skip_weekend: bool,
+    skip_holiday: bool,
+    if_weekend: Option<Shift>,
+    if_holiday: Option<Shift>,
+    from: Option<NaiveDate>,  // FROM 2025-01-01: no occurrences before ...
+    until: Option<NaiveDate>, // ... UNTIL 2025-06-30: and none after
+    except: Vec<NaiveDate>,   // EXCEPT 2025-03-03,2025-04-14
 }
 
-// The part *before* the first comma
-// OR before "msg" or "rem" -- whichever
-// comes first -- is the time indicator
-pub fn split_time_from_description(s_in: &str) -> (String, String) {
-    let max = s_in.len() + 1;
-    let i_msg = s_in.to_lowercase().find(" msg ").unwrap_or(max);
-    let i_rem = s_in.to_lowercase().find(" rem ").unwrap_or(max);
-    let i_comma = s_in.find(", ").unwrap_or(max);
-    if i_comma < max && i_comma < i_msg && i_comma < i_rem {
-        let tmp = s_in.split_once(", ").unwrap();
-        return (tmp.0.to_string(), tmp.1.to_string());
-    };
-    if i_rem < max && i_rem < i_msg && i_rem < i_comma {
-        let tmp = split_once_ignore_case(s_in, " rem ").unwrap();
-        return (tmp.0.to_string(), tmp.1.to_string());
-    }
-    if i_msg < max && i_msg < i_rem && i_msg < i_comma {
-        let tmp = split_once_ignore_case(s_in, " msg ").unwrap();
-        return (tmp.0.to_string(), tmp.1.to_string());
+impl Modifiers {
+    // Is `datum` outside FROM ... UNTIL, or one of the EXCEPT dates?
+    fn excludes(&self, datum: NaiveDate) -> bool {
+        self.from.is_some_and(|from| datum < from)
+            || self.until.is_some_and(|until| datum > until)
+            || self.except.contains(&datum)
     }
-    ("".to_string(), s_in.to_string())
-}
 
-// Dies hier funktioniert nicht gut, 3.12.2025 at 15:00 klappt zwar,
-// aber 3.12.2025 15:00 nicht. Besser wäre eine Trennung von Zeit und
-// Info z.B. durch "," oder "msg"
-// Wenn davor eine Zeit steht, ist es eine Uhrzeit etc.
-// Either Jan 24 2024 AT 10:00 DURATION 1, or
-//        Jan 24 2024 AT 10:00-12:00
-//        Jan 24 2024 10:00-12:00
-fn extract_duration(s_in: &str) -> TimeHelper {
-    // See if we have AT ... - ...
-    // let small = s_in.to_lowercase();
-    let small = split_time_from_description(s_in)
-        .0
-        .to_lowercase()
-        .replace(" - ", "-"); // 10:00 - 11:00 ==> 10:00-11:00
-    let words: Vec<&str> = small.split_whitespace().collect();
-
-    // print!("{:?}", words);
-    for word in words {
-        if word.contains("-") {
-            let times = word.split_once("-").unwrap_or_default();
-            // println!("HEREL {:?}", times);
-            return TimeHelper {
-                start: NaiveTime::parse_from_str(times.0.trim(), &get_time_parser(times.0.trim()))
-                    .ok(),
-                stop: NaiveTime::parse_from_str(times.1.trim(), &get_time_parser(times.1.trim()))
-                    .ok(),
+    // Adjusted date of an occurrence on `datum`, None if it is skipped
+    fn adjust(
+        &self,
+        datum: NaiveDate,
+        is_holiday: &dyn Fn(NaiveDate) -> bool,
+    ) -> Option<NaiveDate> {
+        let is_weekend = |d: NaiveDate| d.weekday().number_from_monday() > 5;
+        if (self.skip_weekend && is_weekend(datum))
+            || (self.skip_holiday && is_holiday(datum))
+            || self.excludes(datum)
+        {
+            return None;
+        }
+        let mut adjusted = datum;
+        // Christmas on a Saturday may have to go back to Thursday
+        for _ in 0..14 {
+            let shift = match (is_weekend(adjusted), is_holiday(adjusted)) {
+                (true, _) if self.if_weekend.is_some() => self.if_weekend,
+                (_, true) if self.if_holiday.is_some() => self.if_holiday,
+                _ => return Some(adjusted),
+            };
+            adjusted = match shift {
+                Some(Shift::Before) => adjusted.pred_opt()?,
+                _ => adjusted.succ_opt()?,
             };
         }
-        if word.contains(":") {
-            return TimeHelper {
-                start: NaiveTime::parse_from_str(word.trim(), &get_time_parser(word.trim())).ok(),
-                stop: None,
+        Some(adjusted)
+    }
+}
+
+// Line modifiers are applied to the date the parsers computed:
+// SKIP-WEEKEND and SKIP-HOLIDAY drop occurrences on weekends or public
+// holidays, IF-WEEKEND and IF-HOLIDAY (BEFORE or AFTER) move them to
+// the previous or next working day; FROM, UNTIL and EXCEPT drop
+// occurrences outside the validity window.
+fn apply_modifiers(
+    spec: &LineSpec,
+    mut termin: Appointment,
+    start_date: Option<NaiveDate>,
+    is_holiday: &dyn Fn(NaiveDate) -> bool,
+) -> Appointment {
+    let modifiers = &spec.modifiers;
+    if *modifiers == Modifiers::default() {
+        return termin;
+    }
+    if modifiers.if_weekend.is_some() || modifiers.if_holiday.is_some() {
+        // An occurrence shortly before or after the requested day
+        // may have been moved to it
+        let datum = start_date.unwrap_or(chrono::offset::Local::now().date_naive());
+        for offset in -7..=7 {
+            let Some(base) = datum.checked_add_signed(TimeDelta::days(offset)) else {
+                continue;
             };
+            let mut moved = get_termin_from_spec(spec, Some(base));
+            if moved.appointment_date == Some(base)
+                && modifiers.adjust(base, is_holiday) == Some(datum)
+            {
+                moved.appointment_date = Some(datum);
+                return moved;
+            }
         }
     }
+    termin.appointment_date = termin
+        .appointment_date
+        .and_then(|datum| modifiers.adjust(datum, is_holiday));
+    termin
+}
 
-    // // We're looking for notations with "-" first,
-    // // i.e. 10:00-11:30 rather than 10:00 DURATION 90
-    // if let Some(index) = small.find(" at ") {
-    //     let start = index + " at ".len();
-    //     let end = match small.len() >= start + "10:00 - 11:00".len() {
-    //         true => start + "10:00 - 11:00".len(),
-    //         _ => start + "10:00-11:00".len(),
-    //     };
-
-    //     // Avoid getting in the middle of unicode bytes
-    //     let text_vec = s_in.chars().collect::<Vec<_>>();
-    //     let s_i = text_vec[start..end].iter().cloned().collect::<String>();
-
-    //     // Is the duration indicated with "-", i.e. 10:00-11:00 ?
-    //     if s_i.contains("-") {
-    //         let times = s_i.split_once("-").unwrap();
-    //         return TimeHelper {
-    //             start: NaiveTime::parse_from_str(times.0.trim(), &get_time_parser(times.0.trim()))
-    //                 .ok(),
-    //             stop: get_stop(times.1.trim()),
-    //         };
-    //     }
-    // }
+fn is_modifier(word: &str) -> bool {
+    ["skip-holiday", "skip-weekend", "if-holiday", "if-weekend"]
+        .iter()
+        .any(|modifier| word.eq_ignore_ascii_case(modifier))
+}
 
-    // This works for "DURATION" notation
-    return TimeHelper {
-        start: extract_start_time(s_in),
-        stop: extract_stop_time(s_in),
+/// Placeholders in descriptions of yearly appointments with a
+/// first year: {age} is the number of years, {nth} the same as an
+/// ordinal number. "Heiko turns {age}", 45 => "Heiko turns 45",
+/// "{nth} wedding anniversary", 10 => "10th wedding anniversary"
+pub(crate) fn expand_placeholders(description: &str, years: i32) -> String {
+    description
+        .replace("{age}", &years.to_string())
+        .replace("{nth}", &as_ordinal(years))
+}
+
+// 1 => 1st, 2 => 2nd, 3 => 3rd, 11 => 11th, 21 => 21st
+fn as_ordinal(n: i32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
     };
+    format!("{n}{suffix}")
 }
 
-// Helper to determine if we're dealing
-// with "10.15" or "10:15" (both are ok)
-fn get_time_parser(s_in: &str) -> String {
-    match s_in.contains(":") {
-        true => "%H:%M".to_string(),
-        _ => "%H.%M".to_string(),
-    }
+// Words in the spec that look like a time or a period of time:
+// "10:00", "10.00-11.30", "3pm", "9am-11:30am", "noon"
+pub(crate) fn get_time_words(s_in: &str) -> Vec<String> {
+    tokenize(s_in)
+        .into_iter()
+        .map(|token| token.text.to_lowercase())
+        .filter(|word| is_time_word(word))
+        .collect()
 }
 
-// The end time could end like this: "-8:00 M", or "-8:00, get up"
-// .get_stop will trim these to "8:00"
-// Input (`excerpt`) must already be trimmed
-fn get_stop(excerpt: &str) -> Option<NaiveTime> {
-    match excerpt.contains(" ") {
-        true => {
-            let candidate = between(excerpt, "", " ");
-            let parser = get_time_parser(candidate);
-            NaiveTime::parse_from_str(candidate, &parser).ok()
-        }
-        _ => match excerpt.contains(",") {
-            true => {
-                let candidate = between(excerpt, "", ",");
-                let parser = get_time_parser(candidate);
-                NaiveTime::parse_from_str(candidate, &parser).ok()
-            }
-            _ => NaiveTime::parse_from_str(excerpt, "%H:%M").ok(),
-        },
+/// "10:15", "10.15", "3pm", "10:30am", "12 a.m.", "noon", "midnight"
+pub(crate) fn parse_time(s_in: &str) -> Option<NaiveTime> {
+    let small = s_in
+        .trim()
+        .trim_end_matches(',')
+        .to_lowercase()
+        .replace('.', ":");
+    match small.as_str() {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
     }
+    // "a:m:" is what became of "a.m."
+    let (clock, pm) = match small.trim_end_matches(':').replace(":m", "m") {
+        t if t.ends_with("am") => (
+            t.trim_end_matches("am").trim_end_matches(':').to_string(),
+            Some(false),
+        ),
+        t if t.ends_with("pm") => (
+            t.trim_end_matches("pm").trim_end_matches(':').to_string(),
+            Some(true),
+        ),
+        t => (t, None),
+    };
+    let Some(pm) = pm else {
+        return NaiveTime::parse_from_str(&clock, "%H:%M").ok();
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    if !(1..=12).contains(&hour) {
+        return None;
+    }
+    NaiveTime::from_hms_opt(hour % 12 + if pm { 12 } else { 0 }, minute, 0)
 }
 
-//// Read yearly appointments, such as
-//// jan 7 msg birthday ds
-////
-//// Returns a "Termin" adding the current year -- or None,
-//// it his is not notation without year.
-pub(crate) fn get_termin_without_year(
-    s_in: &str,
-    start_date: Option<NaiveDate>,
-) -> Option<Appointment> {
-    let words: Vec<&str> = s_in.split_whitespace().collect();
-    if is_month(words.get(0)?) {
-        let year = match start_date {
-            None => chrono::offset::Local::now().date_naive().year(),
-            Some(yr) => yr.year(),
-        };
-        let month = get_month_as_no(s_in)?;
-        if let Ok(day) = strip_final_comma(words.get(1)?).parse::<usize>() {
-            let da = NaiveDate::from_ymd_opt(year, month as u32, day as u32);
-
-            let t: TimeHelper = extract_duration(s_in);
-            return Some(Appointment {
-                appointment_date: da,
-                appointment_is_full_date: false,
-                appointment_start: t.start,
-                appointment_stop: t.stop,
-                appointment_description: extract_description(s_in),
-                appointment_date_alt_text: extract_datum_text(s_in),
-                appointment_color: Some(COLOR_BLUE.to_owned()),
-            });
-        }
-    }
-    None
+// In "10-11:30am", the start takes "am" from the end
+fn parse_range_start(from: &str, to: &str) -> Option<NaiveTime> {
+    parse_time(from).or_else(|| {
+        let to = to.to_lowercase();
+        let suffix = ["am", "pm", "a.m.", "p.m."]
+            .into_iter()
+            .find(|s| to.ends_with(s))?;
+        parse_time(&format!("{from}{suffix}"))
+    })
 }
 
 // Nov 24, Sunday: I want to be able to understand "24," as "24", so
@@ -279,47 +310,14 @@ fn strip_final_comma(get: &str) -> &str {
     }
 }
 
-//// Read weekly appointments, such as
-//// Mon msg buy lunch
-////
-//// Returns a "Termin" adding the current year and month -- or None,
-//// if s_in does not start with a weekday
-pub(crate) fn get_termin_without_month(
-    s_in: &str,
-    start_date: Option<NaiveDate>,
-) -> Option<Appointment> {
-    // let small = s_in.to_lowercase();
-    let termin = split_time_from_description(s_in).0;
-    let words: Vec<&str> = termin.split_whitespace().collect();
-    if is_day(words.get(0)?) {
-        if let Some(da) = find_next_date(words.get(0)?, start_date) {
-            let t: TimeHelper = extract_duration(s_in);
-            return Some(Appointment {
-                appointment_date: Some(da),
-                appointment_is_full_date: false,
-                appointment_start: t.start,
-                appointment_stop: t.stop,
-                appointment_description: extract_description(s_in),
-                appointment_date_alt_text: extract_datum_text(s_in),
-                appointment_color: None,
-            });
-        }
-    }
-    None
-}
-
-/// Find the next date of this weekday
+/// Find the next date on one of these weekdays
 /// If today, for example, is Sat, Oct 5, 2024,
-/// and weekday is "mon", the method returns
+/// and weekdays are [Mon, Wed], the method returns
 /// Oct 7, 2024
-fn find_next_date(weekday: &str, start_date: Option<NaiveDate>) -> Option<NaiveDate> {
-    let mut target_date = match start_date {
-        None => chrono::offset::Local::now().date_naive(),
-        Some(dtm) => dtm,
-    };
-    let wd = &weekday[0..3].to_lowercase(); // Wednesday and Wed are both ok as input
+fn find_next_date(weekdays: &[Weekday], start_date: NaiveDate) -> Option<NaiveDate> {
+    let mut target_date = start_date;
     for _ii in 0..7 {
-        if target_date.weekday().to_string().to_lowercase() == *wd {
+        if weekdays.contains(&target_date.weekday()) {
             return Some(target_date);
         }
         target_date = target_date.checked_add_days(Days::new(1)).unwrap();
@@ -327,24 +325,127 @@ fn find_next_date(weekday: &str, start_date: Option<NaiveDate>) -> Option<NaiveD
     None
 }
 
-pub fn is_month(month: &str) -> bool {
-    MONTHS.iter().any(|s| month.to_lowercase().starts_with(*s))
+/// Weekdays of a weekly appointment: "Mon" (or "Monday"), a range
+/// "Mon-Fri", a list "Mon,Wed,Fri" (also "Mon-Wed,Fri"), or "daily"
+pub(crate) fn parse_weekdays(word: &str) -> Option<Vec<Weekday>> {
+    if word.eq_ignore_ascii_case("daily") {
+        return Some(
+            DAYS.iter()
+                .filter_map(|d| d.parse::<Weekday>().ok())
+                .collect(),
+        );
+    }
+    let mut weekdays: Vec<Weekday> = vec![];
+    for item in word.split(',') {
+        match item.split_once('-') {
+            Some((from, to)) => {
+                let (mut day, last) = (get_weekday(from)?, get_weekday(to)?);
+                weekdays.push(day);
+                while day != last {
+                    day = day.succ();
+                    weekdays.push(day);
+                }
+            }
+            None => weekdays.push(get_weekday(item)?),
+        }
+    }
+    Some(weekdays)
+}
+
+// The first day `offset` days from `anchor` on or after `start`
+// (easter-2, advent1)
+fn get_anchor_occurrence(anchor: &Anchor, offset: i64, start: NaiveDate) -> Option<NaiveDate> {
+    // advent4+10 of last year may well be in January of this year
+    (start.year() - 1..=start.year() + 1)
+        .filter_map(|year| get_anchor_date(anchor, year))
+        .filter_map(|dtm| dtm.checked_add_signed(TimeDelta::try_days(offset)?))
+        .find(|dtm| *dtm >= start)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Anchor {
+    Easter,
+    Advent(u32), // 1st to 4th Sunday of Advent
 }
 
-fn get_month_as_no(month: &str) -> Option<usize> {
+// "easter" => (Easter, 0), "easter+49" => (Easter, 49),
+// "advent1" => (Advent(1), 0), "advent4-1" => (Advent(4), -1)
+fn parse_anchor(word: &str) -> Option<(Anchor, i64)> {
+    let small = strip_final_comma(word).to_lowercase();
+    let (anchor, offset) = match small.find(['+', '-']) {
+        Some(index) => (&small[..index], small[index..].parse::<i64>().ok()?),
+        None => (&small[..], 0),
+    };
+    match anchor {
+        "easter" => Some((Anchor::Easter, offset)),
+        "advent1" => Some((Anchor::Advent(1), offset)),
+        "advent2" => Some((Anchor::Advent(2), offset)),
+        "advent3" => Some((Anchor::Advent(3), offset)),
+        "advent4" => Some((Anchor::Advent(4), offset)),
+        _ => None,
+    }
+}
+
+fn get_anchor_date(anchor: &Anchor, year: i32) -> Option<NaiveDate> {
+    match anchor {
+        Anchor::Easter => easter_sunday(year),
+        Anchor::Advent(n) => {
+            // The fourth Sunday of Advent is the last Sunday before Christmas
+            let christmas_eve = NaiveDate::from_ymd_opt(year, 12, 24)?;
+            let advent4 = christmas_eve.checked_sub_days(Days::new(
+                christmas_eve.weekday().num_days_from_sunday() as u64,
+            ))?;
+            advent4.checked_sub_days(Days::new(7 * (4 - *n as u64)))
+        }
+    }
+}
+
+/// Easter Sunday of `year` (Gregorian calendar; "anonymous
+/// Gregorian algorithm" by Meeus/Jones/Butcher)
+pub(crate) fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+pub(crate) fn get_month_as_no(month: &str) -> Option<usize> {
+    let small = month.to_lowercase();
     match MONTHS
         .iter()
-        .position(|mmonth| month.to_lowercase().starts_with(mmonth))
+        .position(|mmonth| small.starts_with(mmonth))
+        .or(DE_MONTHS
+            .iter()
+            .position(|mmonth| small.starts_with(mmonth)))
     {
         Some(i) => Some(i + 1),
+        _ if small.starts_with("mrz") => Some(3),
         _ => None,
     }
 }
 
 pub fn is_day(weekday_name: &str) -> bool {
-    // let wdn = &weekday_name[0..2];
-    DAYS.iter()
-        .any(|s| weekday_name.to_lowercase().starts_with(*s))
+    get_weekday(weekday_name).is_some()
+}
+
+/// "Mon", "monday", "Mo", "Montag" => Weekday::Mon
+pub(crate) fn get_weekday(weekday_name: &str) -> Option<Weekday> {
+    let small = weekday_name.to_lowercase();
+    let i = DAYS.iter().position(|s| small.starts_with(*s)).or(DE_DAYS
+        .iter()
+        .position(|names| names.contains(&small.trim_end_matches('.'))))?;
+    DAYS[i].parse::<Weekday>().ok()
 }
 
 // fn get_day_as_no(weekday_name: &str) -> Option<usize> {
@@ -353,94 +454,43 @@ pub fn is_day(weekday_name: &str) -> bool {
 //         _ => None,
 //     }
 // }
-// Wird in der Zeile entweder durch "MSG" oder durch "REM"
-// eingeleitet
-// new: MSG, REM, or ","
-pub(crate) fn extract_description(line: &str) -> String {
-    match get_right_from(line, vec![" msg ", " rem ", ", "]) {
-        Some(s) => s,
-        None => NO_INFO.to_string(),
-    }
+/// Value of a setting in the header of a .rem-file, i.e. in the
+/// comment lines at its top:
+/// "# color=red\n# warn=3\nnov 6, birthday", "warn" => Some("3")
+pub(crate) fn get_file_header(termine_aus_datei: &str, key: &str) -> Option<String> {
+    termine_aus_datei
+        .lines()
+        .take_while(|line| line.starts_with('#'))
+        .filter_map(|line| line.trim_start_matches('#').trim().split_once('='))
+        .find(|(k, _)| k.trim().eq_ignore_ascii_case(key))
+        .map(|(_, value)| value.trim().to_string())
 }
 
-// If there's a splitter in the `line`, the text to the
-// right of the splitter is returned.
-// line = "2024 Nov 1 msg what?", splitter ["this", "that", "msg"] => Some(what?)
-fn get_right_from(line: &str, splitters: Vec<&str>) -> Option<String> {
-    let small = line.to_lowercase();
-    for splitter in splitters {
-        // println!("Looking for >{splitter}< in >{small}<");
-        if let Some(index) = small.find(splitter) {
-            return Some(
-                String::from_utf8(line.as_bytes()[index + splitter.len()..].to_vec()).unwrap(),
-            );
-        }
+/// Advance warning: "nov 6 WARN 7, Heiko's birthday" is listed
+/// on the seven days before Nov 6, too => Some(7). Without `WARN n`,
+/// the `# warn=n` of its file (`file_warn`) holds -- but only for
+/// appointments with a date or once a year, not weekly or daily ones.
+pub(crate) fn get_warn_days(s_in: &str, file_warn: Option<u64>) -> Option<u64> {
+    let spec = parse_line_leniently(s_in).ok()?;
+    match spec.date {
+        _ if spec.warn.is_some() => spec.warn,
+        DateSpec::Weekly(_) => None,
+        _ => file_warn,
     }
-
-    None
 }
 
-// textual representation of the date, i.e.
-// Wed, or Jan 10, or 2024 Jan 10
-// This is for display of an appointment
-// found through the search functionality
-pub(crate) fn extract_datum_text(line: &str) -> String {
-    // Mon AT
-    // mar 1 AT
-    // 2024 sep 1 AT
-    match line.split_once(" AT ") {
-        Some(text) => text.0.trim().to_string(),
-        _ => String::new(),
-    }
-}
-
-// AT XYZ DURATION dd
-pub(crate) fn extract_stop_time(line: &str) -> Option<NaiveTime> {
-    let small = line.to_lowercase();
-    if let Some(start) = extract_start_time(&small) {
-        let mut duration = between(&small, " duration ", " ").replace(",", "."); // decimal point Engl.
-
-        // If duration is 1.5 (for 1.5 hours),
-        // we'll try to convert this to minutes here:
-        if duration.contains(".") {
-            let f = duration.parse::<f64>().unwrap_or(0.0);
-            let i: i64 = (f * 60.0).round() as i64;
-            duration = format!("{i}");
-        }
-
-        if let Ok(f_duration) = (duration.replace(",", ".")).parse::<i64>() {
-            if f_duration > 8 {
-                // werten wir als Minuten
-                let (r, _) =
-                    start.overflowing_add_signed(TimeDelta::try_minutes(f_duration).unwrap());
-                return Some(r);
-            } else {
-                // werten wir als Stunden
-                let (r, _) =
-                    start.overflowing_add_signed(TimeDelta::try_hours(f_duration).unwrap());
-
-                return Some(r);
-            }
-        }
-    }
-    None
-    // todo!()
-}
-
-// AT 11:00
-pub(crate) fn extract_start_time(line: &str) -> Option<NaiveTime> {
-    let small = line.to_lowercase();
-    // @todo: change syntax: " at " is too common to use as identifyer
-    let s = between(&small, " at ", " ");
-    match NaiveTime::parse_from_str(s, "%H:%M") {
-        Ok(r) => Some(r),
-        _ => match NaiveTime::parse_from_str(s, "%H.%M") {
-            Ok(r2) => Some(r2),
-            _ => None,
-        },
-    }
-    // None
-    // todo!()
+/// Tags are words in the description that start with '#':
+/// "dentist #health #Family" => ["health", "family"]
+pub(crate) fn get_tags(description: &str) -> Vec<String> {
+    description
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| {
+            tag.trim_end_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|tag| !tag.is_empty())
+        .collect()
 }
 
 // pub(crate) fn get_full_month(monat: u32) -> String {
@@ -491,13 +541,51 @@ mod test_parsing {
     use crate::{
         Appointment,
         parser::{
-            get_month_as_no, get_termin_from_full_date, get_termin_from_line,
-            get_termin_without_month, get_termin_without_year, is_date, is_month, parse_date,
+            DateSpec, apply_modifiers, as_ordinal, easter_sunday, get_file_header, get_month_as_no,
+            get_tags, get_termin_from_line, get_termin_from_spec, get_warn_days, is_date, is_day,
+            parse_line, parse_time, parse_weekdays,
         },
     };
 
     // use super::NO_INFO;
 
+    fn is_month(month: &str) -> bool {
+        get_month_as_no(month).is_some()
+    }
+
+    // The appointment of the line, if its date is of the kind asked for
+    fn get_termin_if(
+        s_test: &str,
+        start_date: Option<NaiveDate>,
+        kind: fn(&DateSpec) -> bool,
+    ) -> Option<Appointment> {
+        let spec = parse_line(s_test).ok()?;
+        kind(&spec.date).then(|| get_termin_from_spec(&spec, start_date))
+    }
+
+    fn get_termin_from_full_date(s_test: &str, start: Option<NaiveDate>) -> Option<Appointment> {
+        get_termin_if(s_test, start, |date| {
+            matches!(date, DateSpec::Full(_) | DateSpec::Anniversary(_))
+        })
+    }
+
+    fn get_termin_without_year(s_test: &str, start: Option<NaiveDate>) -> Option<Appointment> {
+        get_termin_if(s_test, start, |date| {
+            matches!(date, DateSpec::Yearly { .. })
+        })
+    }
+
+    fn get_termin_without_month(s_test: &str, start: Option<NaiveDate>) -> Option<Appointment> {
+        get_termin_if(s_test, start, |date| matches!(date, DateSpec::Weekly(_)))
+    }
+
+    fn parse_date(s_test: &str) -> Option<NaiveDate> {
+        match parse_line(s_test).ok()?.date {
+            DateSpec::Full(datum) | DateSpec::Anniversary(datum) => Some(datum),
+            _ => None,
+        }
+    }
+
     fn get_testtermin_thisyear() -> Appointment {
         let year = offset::Local::now().date_naive().year();
         Appointment {
@@ -508,6 +596,11 @@ mod test_parsing {
             appointment_description: "birthday".to_string(), //NO_INFO.to_string(),
             appointment_date_alt_text: "".to_string(),
             appointment_color: None,
+            appointment_file: None,
+            appointment_days_ahead: None,
+            appointment_origin_year: None,
+            appointment_zone: None,
+            appointment_id: None,
         }
     }
 
@@ -629,9 +722,9 @@ mod test_parsing {
     fn parsing_full_date() {
         let s_test = "2024 nov 6 aT 10:00 DURATION 1 msg my birthday";
         // assert!(get_termin_without_year(&s_test).is_none());
-        assert!(get_termin_from_full_date(&s_test).is_some());
+        assert!(get_termin_from_full_date(s_test, None).is_some());
         assert_eq!(
-            get_termin_from_full_date(&s_test)
+            get_termin_from_full_date(s_test, None)
                 .unwrap()
                 .appointment_date
                 .unwrap()
@@ -639,7 +732,7 @@ mod test_parsing {
             10
         );
         assert_eq!(
-            get_termin_from_full_date(&s_test)
+            get_termin_from_full_date(s_test, None)
                 .unwrap()
                 .appointment_start
                 .unwrap(),
@@ -860,6 +953,119 @@ mod test_parsing {
         );
     }
 
+    #[test]
+    fn parsing_easter() {
+        assert_eq!(easter_sunday(2000), NaiveDate::from_ymd_opt(2000, 4, 23));
+        assert_eq!(easter_sunday(2024), NaiveDate::from_ymd_opt(2024, 3, 31));
+        assert_eq!(easter_sunday(2025), NaiveDate::from_ymd_opt(2025, 4, 20));
+        assert_eq!(easter_sunday(2026), NaiveDate::from_ymd_opt(2026, 4, 5));
+    }
+
+    #[test]
+    fn parsing_anchors() {
+        let jan1 = NaiveDate::from_ymd_opt(2025, 1, 1);
+        let s_test = "easter-2 AT 15:00, Karfreitag";
+        let tmp = get_termin_from_line(s_test, jan1).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 4, 18));
+        assert_eq!(tmp.appointment_description, "Karfreitag");
+        assert_eq!(
+            tmp.appointment_start,
+            NaiveTime::parse_from_str("15:00", "%H:%M").ok()
+        );
+
+        let s_test = "easter+60, Fronleichnam";
+        let tmp = get_termin_from_line(s_test, jan1).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 6, 19));
+
+        let s_test = "advent1 REM first candle";
+        let tmp = get_termin_from_line(s_test, jan1).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 11, 30));
+
+        // Dec 24, 2022 was a Saturday
+        let s_test = "advent4, fourth candle";
+        let tmp = get_termin_from_line(s_test, NaiveDate::from_ymd_opt(2022, 1, 1)).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2022, 12, 18));
+
+        // advent4+14 of 2024 is in 2025
+        let s_test = "advent4+14, after the holidays";
+        let tmp = get_termin_from_line(s_test, jan1).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 1, 5));
+
+        assert!(get_termin_from_line("eastern, x", jan1).is_none());
+    }
+
+    #[test]
+    fn parsing_anniversary() {
+        let s_test = "1980 nov 6 YEARLY, Heiko turns {age}";
+        let tmp = get_termin_from_line(s_test, NaiveDate::from_ymd_opt(2025, 1, 1)).unwrap();
+        assert!(!tmp.appointment_is_full_date);
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 11, 6));
+        assert_eq!(tmp.appointment_origin_year, Some(1980));
+        assert_eq!(tmp.get_description(), "Heiko turns 45");
+
+        let s_test = "12.6.2015 yearly AT 19:00, {nth} wedding anniversary";
+        let tmp = get_termin_from_line(s_test, NaiveDate::from_ymd_opt(2025, 1, 1)).unwrap();
+        assert_eq!(tmp.get_description(), "10th wedding anniversary");
+        assert!(tmp.appointment_start.is_some());
+        assert!(
+            get_termin_from_line(s_test, NaiveDate::from_ymd_opt(2014, 1, 1))
+                .unwrap()
+                .appointment_date
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn parsing_ordinal() {
+        assert_eq!(as_ordinal(1), "1st");
+        assert_eq!(as_ordinal(12), "12th");
+        assert_eq!(as_ordinal(22), "22nd");
+        assert_eq!(as_ordinal(103), "103rd");
+    }
+
+    #[test]
+    fn parsing_warn() {
+        assert_eq!(
+            get_warn_days("nov 6 WARN 7, Heiko's birthday", None),
+            Some(7)
+        );
+        assert_eq!(
+            get_warn_days("nov 6 at 10:00 warn 2 msg party", None),
+            Some(2)
+        );
+        assert_eq!(get_warn_days("nov 6, warn 7 people", None), None);
+        let s_test = "nov 6 WARN 7, Heiko's birthday";
+        assert_eq!(
+            get_termin_from_line(s_test, None)
+                .unwrap()
+                .appointment_description,
+            "Heiko's birthday"
+        );
+        // `# warn=3` of the file: not for weekly or daily appointments
+        assert_eq!(get_warn_days("nov 6, birthday", Some(3)), Some(3));
+        assert_eq!(get_warn_days("2025-05-17, dentist", Some(3)), Some(3));
+        assert_eq!(get_warn_days("nov 6 WARN 0, birthday", Some(3)), Some(0));
+        assert_eq!(get_warn_days("Mon 10:00, standup", Some(3)), None);
+        assert_eq!(get_warn_days("Mon WARN 1, gym", Some(3)), Some(1));
+    }
+
+    #[test]
+    fn parsing_file_header() {
+        let s_test = "# color=red\n#warn = 3\nnov 6, birthday\n# warn=5";
+        assert_eq!(get_file_header(s_test, "color"), Some("red".to_string()));
+        assert_eq!(get_file_header(s_test, "warn"), Some("3".to_string()));
+        assert_eq!(get_file_header(s_test, "tz"), None);
+    }
+
+    #[test]
+    fn parsing_tags() {
+        assert_eq!(
+            get_tags("dentist #health, with #Family."),
+            vec!["health".to_string(), "family".to_string()]
+        );
+        assert!(get_tags("no tags # here").is_empty());
+    }
+
     #[test]
     fn parsing_comprehensive15() {
         let s_test = "Nov 24, Sonntag";
@@ -867,4 +1073,202 @@ mod test_parsing {
         let tmp = get_termin_from_line(&s_test, None).unwrap();
         assert_eq!(tmp.appointment_description, "Sonntag");
     }
+
+    #[test]
+    fn parsing_skip_holiday() {
+        let monday = NaiveDate::from_ymd_opt(2025, 6, 9); // Pfingstmontag
+        let holiday = |d: NaiveDate| Some(d) == monday;
+        let s_test = "Mon 18:00 SKIP-HOLIDAY, choir";
+        let tmp = get_termin_from_line(s_test, monday).unwrap();
+        assert_eq!(tmp.appointment_start, NaiveTime::from_str("18:00:00").ok());
+        let tmp = apply_modifiers(&parse_line(s_test).unwrap(), tmp, monday, &holiday);
+        assert!(tmp.appointment_date.is_none());
+
+        // Without the modifier, holidays do not matter
+        let tmp = get_termin_from_line("Mon 18:00, choir", monday).unwrap();
+        let spec = parse_line("Mon 18:00, choir").unwrap();
+        let tmp = apply_modifiers(&spec, tmp, monday, &holiday);
+        assert_eq!(tmp.appointment_date, monday);
+    }
+
+    #[test]
+    fn parsing_business_days() {
+        let no_holidays = |_: NaiveDate| false;
+        let on = |s_test: &str, y: i32, m: u32, d: u32| {
+            let datum = NaiveDate::from_ymd_opt(y, m, d);
+            let tmp = get_termin_from_line(s_test, datum).unwrap();
+            let spec = parse_line(s_test).unwrap();
+            apply_modifiers(&spec, tmp, datum, &no_holidays).appointment_date == datum
+        };
+        // May 31, 2025 is a Saturday
+        assert!(on("may 31 IF-WEEKEND BEFORE, payroll", 2025, 5, 30));
+        assert!(!on("may 31 IF-WEEKEND BEFORE, payroll", 2025, 5, 31));
+        assert!(on("may 31 IF-WEEKEND AFTER, payroll", 2025, 6, 2));
+        assert!(on("2025 may 31 if-weekend after, invoice", 2025, 6, 2));
+        assert!(!on("may 31 SKIP-WEEKEND, payroll", 2025, 5, 31));
+        assert!(on("may 30 SKIP-WEEKEND, payroll", 2025, 5, 30));
+
+        // Dec 25, 2027 is a Saturday, Dec 24 a holiday
+        let christmas = |d: NaiveDate| d.month() == 12 && (24..=26).contains(&d.day());
+        let datum = NaiveDate::from_ymd_opt(2027, 12, 23);
+        let s_test = "dec 25 IF-WEEKEND BEFORE IF-HOLIDAY BEFORE, deadline";
+        let tmp = get_termin_from_line(s_test, datum).unwrap();
+        let tmp = apply_modifiers(&parse_line(s_test).unwrap(), tmp, datum, &christmas);
+        assert_eq!(tmp.appointment_date, datum);
+    }
+
+    #[test]
+    fn parsing_weekday_sets() {
+        use chrono::Weekday;
+        assert_eq!(
+            parse_weekdays("Mon-Fri"),
+            Some(vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri
+            ])
+        );
+        assert_eq!(
+            parse_weekdays("mon,wed,friday"),
+            Some(vec![Weekday::Mon, Weekday::Wed, Weekday::Fri])
+        );
+        assert_eq!(
+            parse_weekdays("Sat-Mon"),
+            Some(vec![Weekday::Sat, Weekday::Sun, Weekday::Mon])
+        );
+        assert_eq!(parse_weekdays("daily").map(|d| d.len()), Some(7));
+        assert!(parse_weekdays("10:00-11:00").is_none());
+
+        // Oct 18, 2025 is a Saturday
+        let saturday = NaiveDate::from_ymd_opt(2025, 10, 18);
+        let tmp = get_termin_from_line("Mon-Fri AT 09:15, standup", saturday).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 10, 20));
+        assert_eq!(tmp.appointment_start, NaiveTime::from_str("09:15:00").ok());
+        assert_eq!(tmp.appointment_description, "standup");
+
+        let tmp = get_termin_from_line("Mon,Wed,Fri AT 07:00, gym", saturday).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 10, 20));
+
+        let tmp = get_termin_from_line("daily 22:00, meds", saturday).unwrap();
+        assert_eq!(tmp.appointment_date, saturday);
+        assert_eq!(tmp.appointment_start, NaiveTime::from_str("22:00:00").ok());
+    }
+
+    #[test]
+    fn parsing_validity_window() {
+        let s_test = "Mon-Fri FROM 2025-10-01 UNTIL 31.10.2025 EXCEPT 2025-10-03 AT 9:15, standup";
+        let on = |y: i32, m: u32, d: u32| {
+            let datum = NaiveDate::from_ymd_opt(y, m, d);
+            get_termin_from_line(s_test, datum)
+                .unwrap()
+                .appointment_date
+                == datum
+        };
+        assert!(on(2025, 10, 1));
+        assert!(!on(2025, 9, 30));
+        assert!(!on(2025, 10, 3));
+        assert!(on(2025, 10, 31));
+        assert!(!on(2025, 11, 3));
+        let tmp = get_termin_from_line(s_test, NaiveDate::from_ymd_opt(2025, 10, 1)).unwrap();
+        assert_eq!(tmp.appointment_start, NaiveTime::from_str("09:15:00").ok());
+    }
+
+    #[test]
+    fn parsing_german_names() {
+        use chrono::Weekday;
+        assert!(is_month("Mär"));
+        assert!(is_month("Dezember"));
+        assert_eq!(get_month_as_no("Okt"), Some(10));
+        assert_eq!(get_month_as_no("mrz"), Some(3));
+        assert!(is_day("Mo"));
+        assert!(is_day("Donnerstag"));
+        assert!(!is_day("Dinner"));
+        assert_eq!(
+            parse_weekdays("Mo-Mi,Fr"),
+            Some(vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Fri])
+        );
+        let tmp = get_termin_from_line("2025 Dez 24, Heiligabend", None).unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 12, 24));
+        let tmp = get_termin_from_line(
+            "Mär 1 REM Frühlingsanfang",
+            NaiveDate::from_ymd_opt(2025, 1, 1),
+        )
+        .unwrap();
+        assert_eq!(tmp.appointment_date, NaiveDate::from_ymd_opt(2025, 3, 1));
+    }
+
+    #[test]
+    fn parsing_twelve_hour_times() {
+        let t = |h: u32, m: u32| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(parse_time("3pm"), t(15, 0));
+        assert_eq!(parse_time("10:30am"), t(10, 30));
+        assert_eq!(parse_time("12am"), t(0, 0));
+        assert_eq!(parse_time("12:15pm"), t(12, 15));
+        assert_eq!(parse_time("7.45p.m."), t(19, 45));
+        assert_eq!(parse_time("noon"), t(12, 0));
+        assert_eq!(parse_time("Midnight"), t(0, 0));
+        assert_eq!(parse_time("16.15"), t(16, 15));
+        assert!(parse_time("13pm").is_none());
+        assert!(parse_time("25:00").is_none());
+
+        let tmp = get_termin_from_line("Mon 9am-11:30am, workshop", None).unwrap();
+        assert_eq!(
+            (tmp.appointment_start, tmp.appointment_stop),
+            (t(9, 0), t(11, 30))
+        );
+        let tmp = get_termin_from_line("Tue 10-11am, call", None).unwrap();
+        assert_eq!(
+            (tmp.appointment_start, tmp.appointment_stop),
+            (t(10, 0), t(11, 0))
+        );
+        let tmp = get_termin_from_line("Wed AT 3 pm, tea", None).unwrap();
+        assert_eq!(tmp.appointment_start, t(15, 0));
+        let tmp = get_termin_from_line("2025 jun 1 noon-1pm, lunch", None).unwrap();
+        assert_eq!(
+            (tmp.appointment_start, tmp.appointment_stop),
+            (t(12, 0), t(13, 0))
+        );
+
+        assert_eq!(
+            parse_line("Mon 25:00, x").unwrap_err().message,
+            "cannot read the time `25:00`"
+        );
+        assert_eq!(
+            parse_line("Mon 9am-13pm, x").unwrap_err().message,
+            "cannot read the time `9am-13pm`"
+        );
+        assert!(parse_line("Mon-Fri 2025-05-01 9:15, x").is_ok());
+    }
+
+    #[test]
+    fn parsing_with_mistakes() {
+        // A mistake after the date does not hide the appointment
+        let day = NaiveDate::from_ymd_opt(2026, 10, 19);
+        for s_test in [
+            "2026-10-19 AT 25:00, typo meeting",
+            "2026-10-19 AT 10:00 DURATION 1h MSG doctor",
+            "oct 19 until 2pm, deadline",
+            "Mon 10:00 from home MSG remote work",
+            "2026 oct 19 am Bahnhof MSG Treffen",
+        ] {
+            let tmp = get_termin_from_line(s_test, day).unwrap();
+            assert_eq!(tmp.appointment_date, day, "{s_test}");
+        }
+        let tmp = get_termin_from_line("2026-10-19 AT 25:00, typo meeting", day).unwrap();
+        assert_eq!(tmp.appointment_description, "typo meeting");
+        assert_eq!(tmp.appointment_start, None);
+        // ... but `check` sees it
+        assert!(parse_line("2026-10-19 AT 25:00, typo meeting").is_err());
+        assert!(parse_line("2026-10-19 AT 10:00 DURATION 1h MSG doctor").is_err());
+        // "from home", "until 2pm" and "am Bahnhof" are just words
+        assert!(parse_line("Mon 10:00 from home MSG remote work").is_ok());
+        assert!(parse_line("oct 19 until 2pm, deadline").is_ok());
+        assert!(parse_line("2026 oct 19 am Bahnhof MSG Treffen").is_ok());
+        assert_eq!(
+            parse_line("oct 19 10 am, x").unwrap().start,
+            NaiveTime::from_hms_opt(10, 0, 0)
+        );
+    }
 }
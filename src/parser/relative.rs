@@ -0,0 +1,233 @@
+// Dates relative to today, the way they are typed on the command line:
+// "today", "tomorrow", "yesterday", "fri" (the next Friday, today if
+// it is Friday), "next fri", "last fri", "in 3 weeks", "2 days ago",
+// "end of month"; and periods: "this-week", "next-month", "2025-W20",
+// "2025-05", "Q3".
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+use super::get_weekday;
+
+/// The date a phrase at the start of `words` stands for, and how many
+/// words the phrase takes up:
+/// ["next", "fri", "10:00,", "haircut"] => (the next Friday, 2)
+pub(crate) fn parse_relative_date(words: &[&str], today: NaiveDate) -> Option<(NaiveDate, usize)> {
+    let words: Vec<String> = words
+        .iter()
+        .take(3)
+        .map(|w| w.trim_end_matches(',').to_lowercase())
+        .collect();
+    let word = |i: usize| words.get(i).map(|w| w.as_str()).unwrap_or_default();
+
+    match (word(0), word(1), word(2)) {
+        ("day", "after", "tomorrow") => return Some((today.checked_add_days(Days::new(2))?, 3)),
+        ("day", "before", "yesterday") => return Some((today.checked_sub_days(Days::new(2))?, 3)),
+        ("today", _, _) => return Some((today, 1)),
+        ("tomorrow", _, _) => return Some((today.succ_opt()?, 1)),
+        ("yesterday", _, _) => return Some((today.pred_opt()?, 1)),
+        ("in", n, unit) => return Some((shift(today, get_count(n)?, unit)?, 3)),
+        (n, unit, "ago") => return Some((shift(today, -get_count(n)?, unit)?, 3)),
+        ("end" | "start", "of", period) => {
+            return Some((get_period_boundary(today, period, word(0) == "end")?, 3));
+        }
+        _ => {}
+    }
+
+    if let Some(weekday) = get_weekday(word(0)) {
+        return Some((next_weekday(today, weekday, 0), 1));
+    }
+    let weekday = get_weekday(word(1))?;
+    match word(0) {
+        "this" => Some((next_weekday(today, weekday, 0), 2)),
+        "next" => Some((next_weekday(today, weekday, 1), 2)),
+        "last" => {
+            let back =
+                (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday() - 1)
+                    % 7
+                    + 1;
+            Some((today.checked_sub_days(Days::new(back as u64))?, 2))
+        }
+        _ => None,
+    }
+}
+
+/// First and last day of a named period: this-week, next-month,
+/// last-year (week, month, quarter, year), 2025-W20, 2025-05, Q3, 2025-Q3
+pub(crate) fn parse_period(word: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let word = word.to_lowercase();
+    if let Some((which, period)) = word.split_once('-')
+        && let Some(shift) = match which {
+            "last" => Some(-1),
+            "this" => Some(0),
+            "next" => Some(1),
+            _ => None,
+        }
+    {
+        let datum = match period {
+            "week" => shift_days(today, 7 * shift)?,
+            "month" => shift_months(today, shift)?,
+            "quarter" => shift_months(today, 3 * shift)?,
+            "year" => shift_months(today, 12 * shift)?,
+            _ => return None,
+        };
+        return match period {
+            "quarter" => get_quarter(datum.year(), datum.month0() / 3 + 1),
+            _ => Some((
+                get_period_boundary(datum, period, false)?,
+                get_period_boundary(datum, period, true)?,
+            )),
+        };
+    }
+
+    // Q3 (this year) or 2025-Q3
+    let (year, rest) = match word.split_once('-') {
+        Some((year, rest)) => (year.parse::<i32>().ok()?, rest),
+        None => (today.year(), word.as_str()),
+    };
+    if let Some(quarter) = rest.strip_prefix('q') {
+        return get_quarter(year, quarter.parse::<u32>().ok()?);
+    }
+    if rest.len() == word.len() {
+        return None;
+    }
+    // 2025-W20
+    if let Some(week) = rest.strip_prefix('w') {
+        let week = week.parse::<u32>().ok()?;
+        let first = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+        return Some((first, NaiveDate::from_isoywd_opt(year, week, Weekday::Sun)?));
+    }
+    // 2025-05
+    let first = NaiveDate::from_ymd_opt(year, rest.parse::<u32>().ok()?, 1)?;
+    Some((first, get_period_boundary(first, "month", true)?))
+}
+
+fn get_quarter(year: i32, quarter: u32) -> Option<(NaiveDate, NaiveDate)> {
+    if !(1..=4).contains(&quarter) {
+        return None;
+    }
+    let first = NaiveDate::from_ymd_opt(year, 3 * quarter - 2, 1)?;
+    Some((first, first.checked_add_months(Months::new(3))?.pred_opt()?))
+}
+
+// "3" => 3, "a" (in a week) => 1
+fn get_count(word: &str) -> Option<i64> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        _ => word.parse::<i64>().ok(),
+    }
+}
+
+// First `weekday` on or after today (plus `skip` days, so that
+// "next fri" on a Friday is in a week)
+fn next_weekday(today: NaiveDate, weekday: Weekday, skip: u64) -> NaiveDate {
+    let start = today + Days::new(skip);
+    let ahead = (7 + weekday.num_days_from_monday() - start.weekday().num_days_from_monday()) % 7;
+    start + Days::new(ahead as u64)
+}
+
+fn shift(today: NaiveDate, n: i64, unit: &str) -> Option<NaiveDate> {
+    match unit.trim_end_matches('s') {
+        "day" => shift_days(today, n),
+        "week" => shift_days(today, 7 * n),
+        "month" => shift_months(today, n),
+        "year" => shift_months(today, 12 * n),
+        _ => None,
+    }
+}
+
+fn shift_days(datum: NaiveDate, n: i64) -> Option<NaiveDate> {
+    match n >= 0 {
+        true => datum.checked_add_days(Days::new(n as u64)),
+        false => datum.checked_sub_days(Days::new(n.unsigned_abs())),
+    }
+}
+
+fn shift_months(datum: NaiveDate, n: i64) -> Option<NaiveDate> {
+    match n >= 0 {
+        true => datum.checked_add_months(Months::new(n as u32)),
+        false => datum.checked_sub_months(Months::new(n.unsigned_abs() as u32)),
+    }
+}
+
+// start/end of week (Monday to Sunday), month or year
+fn get_period_boundary(today: NaiveDate, period: &str, is_end: bool) -> Option<NaiveDate> {
+    let first = match period {
+        "week" => {
+            today.checked_sub_days(Days::new(today.weekday().num_days_from_monday() as u64))?
+        }
+        "month" => today.with_day(1)?,
+        "year" => NaiveDate::from_ymd_opt(today.year(), 1, 1)?,
+        _ => return None,
+    };
+    match (is_end, period) {
+        (false, _) => Some(first),
+        (true, "week") => first.checked_add_days(Days::new(6)),
+        (true, "month") => first.checked_add_months(Months::new(1))?.pred_opt(),
+        (true, _) => NaiveDate::from_ymd_opt(today.year(), 12, 31),
+    }
+}
+
+#[cfg(test)]
+mod test_relative {
+    use chrono::NaiveDate;
+
+    use super::{parse_period, parse_relative_date};
+
+    fn parse(s: &str) -> Option<(NaiveDate, usize)> {
+        // Saturday
+        let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        parse_relative_date(&s.split_whitespace().collect::<Vec<&str>>(), today)
+    }
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    #[test]
+    fn relative_days() {
+        assert_eq!(parse("today"), Some((date(10, 17), 1)));
+        assert_eq!(parse("tomorrow, dentist"), Some((date(10, 18), 1)));
+        assert_eq!(parse("Yesterday"), Some((date(10, 16), 1)));
+        assert_eq!(parse("day after tomorrow"), Some((date(10, 19), 3)));
+        assert_eq!(parse("sat"), Some((date(10, 17), 1)));
+        assert_eq!(parse("mon 10:00, x"), Some((date(10, 19), 1)));
+        assert_eq!(parse("next sat"), Some((date(10, 24), 2)));
+        assert_eq!(parse("next fri 10:00, haircut"), Some((date(10, 23), 2)));
+        assert_eq!(parse("last sat"), Some((date(10, 10), 2)));
+        assert_eq!(parse("last fri"), Some((date(10, 16), 2)));
+        assert!(parse("next").is_none());
+        assert!(parse("dentist").is_none());
+    }
+
+    #[test]
+    fn relative_periods() {
+        assert_eq!(parse("in 3 weeks"), Some((date(11, 7), 3)));
+        assert_eq!(parse("in a month"), Some((date(11, 17), 3)));
+        assert_eq!(parse("2 days ago"), Some((date(10, 15), 3)));
+        assert_eq!(parse("end of month"), Some((date(10, 31), 3)));
+        assert_eq!(parse("end of week"), Some((date(10, 18), 3)));
+        assert_eq!(parse("start of week"), Some((date(10, 12), 3)));
+        assert_eq!(parse("end of year"), Some((date(12, 31), 3)));
+        assert!(parse("in 3 fortnights").is_none());
+    }
+
+    #[test]
+    fn relative_named_periods() {
+        let today = date(10, 17);
+        let period = |s: &str| parse_period(s, today);
+        assert_eq!(period("this-week"), Some((date(10, 12), date(10, 18))));
+        assert_eq!(period("next-week"), Some((date(10, 19), date(10, 25))));
+        assert_eq!(period("next-month"), Some((date(11, 1), date(11, 30))));
+        assert_eq!(period("last-quarter"), Some((date(7, 1), date(9, 30))));
+        assert_eq!(period("Q1"), Some((date(1, 1), date(3, 31))));
+        let d = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(period("2025-Q3"), Some((d(2025, 7, 1), d(2025, 9, 30))));
+        assert_eq!(period("2025-W20"), Some((d(2025, 5, 12), d(2025, 5, 18))));
+        assert_eq!(period("2025-05"), Some((d(2025, 5, 1), d(2025, 5, 31))));
+        assert_eq!(period("2024-02"), Some((d(2024, 2, 1), d(2024, 2, 29))));
+        assert!(period("Q5").is_none());
+        assert!(period("2025").is_none());
+        assert!(period("dentist").is_none());
+        assert!(period("2025-05-01").is_none());
+    }
+}
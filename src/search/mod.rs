@@ -0,0 +1,283 @@
+// Searching appointments, for `when` and `when_was`
+//
+// By default, the search term is looked for in the description of an
+// appointment, ignoring case. `--case-sensitive` respects case,
+// `--whole-line` also looks at the date and time part of the line.
+// `--regex` takes the search term as a regular expression, `--fuzzy`
+// accepts the letters of the search term with gaps in between
+// ("hbd" finds "Heiko's birthday") and ranks the results.
+
+use chrono::{Days, NaiveDate};
+use chrono_tz::Tz;
+use regex::{Regex, RegexBuilder};
+
+use crate::{Appointment, parser::get_termin_from_line, timezone::to_viewer_zone};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum SearchMode {
+    Plain,
+    Regex,
+    Fuzzy,
+}
+
+#[derive(Debug)]
+pub(crate) struct Matcher {
+    term: String,
+    mode: SearchMode,
+    case_sensitive: bool,
+    whole_line: bool,
+    regex: Option<Regex>,
+}
+
+impl Matcher {
+    pub(crate) fn new(
+        term: &str,
+        mode: SearchMode,
+        case_sensitive: bool,
+        whole_line: bool,
+    ) -> Result<Matcher, String> {
+        let regex = match mode {
+            SearchMode::Regex => Some(
+                RegexBuilder::new(term)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .map_err(|e| {
+                        format!("Sorry, `{term}` is not a valid regular expression: {e}")
+                    })?,
+            ),
+            _ => None,
+        };
+        Ok(Matcher {
+            term: match case_sensitive {
+                true => term.to_string(),
+                false => term.to_lowercase(),
+            },
+            mode,
+            case_sensitive,
+            whole_line,
+            regex,
+        })
+    }
+
+    /// `rremind when dentist --regex --case-sensitive --whole-line`
+    pub(crate) fn from_args(term: &str, args: &[String]) -> Result<Matcher, String> {
+        let has_flag = |flag: &str| args.iter().any(|arg| arg == flag);
+        let mode = match (has_flag("--regex"), has_flag("--fuzzy")) {
+            (true, true) => return Err("Please use either --regex or --fuzzy.".to_string()),
+            (true, false) => SearchMode::Regex,
+            (false, true) => SearchMode::Fuzzy,
+            _ => SearchMode::Plain,
+        };
+        Matcher::new(
+            term,
+            mode,
+            has_flag("--case-sensitive"),
+            has_flag("--whole-line"),
+        )
+    }
+
+    pub(crate) fn is_fuzzy(&self) -> bool {
+        self.mode == SearchMode::Fuzzy
+    }
+
+    /// Does the appointment in `line` (with `description`) match?
+    pub(crate) fn matches(&self, line: &str, description: &str) -> bool {
+        match self.whole_line {
+            true => self.score(line).is_some(),
+            false => self.score(description).is_some(),
+        }
+    }
+
+    /// `None` if `text` does not match; otherwise a score that is
+    /// higher the better the match (only fuzzy matches differ here).
+    pub(crate) fn score(&self, text: &str) -> Option<i64> {
+        if self.term.is_empty() {
+            return Some(0);
+        }
+        let text = match self.case_sensitive {
+            true => text.to_string(),
+            false => text.to_lowercase(),
+        };
+        match self.mode {
+            SearchMode::Plain => text.contains(&self.term).then_some(0),
+            SearchMode::Regex => self.regex.as_ref()?.is_match(&text).then_some(0),
+            SearchMode::Fuzzy => fuzzy_score(&self.term, &text),
+        }
+    }
+}
+
+// The characters of `pattern` must appear in `text` in this order.
+// Every hit scores; hits that follow each other directly, or that are
+// at the beginning of a word, score more; gaps cost a little.
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score: i64 = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for p in pattern.chars() {
+        let found = (position..text.len()).find(|i| text[*i] == p)?;
+        score += 16;
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 10;
+        }
+        match previous {
+            Some(prev) if prev + 1 == found => score += 15,
+            Some(prev) => score -= (found - prev - 1) as i64,
+            None => score -= found as i64,
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// The search term of `rremind when --fuzzy hbd` or `rremind when_was
+/// --file work dentist`: the first word after the command that is neither
+/// a flag nor the value of one
+pub(crate) fn get_search_term(args: &[String]) -> Option<String> {
+    let mut words = args.iter().skip(2);
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "--file" | "--tag" | "--since" | "--until" => {
+                words.next();
+            }
+            flag if flag.starts_with("--") => {}
+            term => return Some(term.to_string()),
+        }
+    }
+    None
+}
+
+/// The occurrences of the appointment in `line` that `when` lists, from
+/// `today` on: one with a full date if it is not past; a recurring one
+/// with its next `max` occurrences within `horizon` days. With a `zone`,
+/// they are converted to the zone of the viewer.
+pub(crate) fn get_occurrences(
+    line: &str,
+    today: NaiveDate,
+    horizon: u64,
+    max: usize,
+    zone: Option<Tz>,
+) -> Vec<Appointment> {
+    let in_viewer_zone = |mut termin: Appointment| {
+        if let Some(zone) = zone {
+            to_viewer_zone(&mut termin, zone);
+        }
+        termin
+    };
+    let Some(found) = get_termin_from_line(line, Some(today)) else {
+        return vec![];
+    };
+    if found.appointment_is_full_date {
+        return Some(in_viewer_zone(found))
+            .filter(|termin| termin.appointment_date.is_some_and(|dtm| dtm >= today))
+            .into_iter()
+            .collect();
+    }
+
+    (0..=horizon)
+        .filter_map(|days| today.checked_add_days(Days::new(days)))
+        .filter_map(|datum| {
+            get_termin_from_line(line, Some(datum))
+                .filter(|next| next.appointment_date == Some(datum))
+        })
+        .take(max)
+        .map(in_viewer_zone)
+        .collect()
+}
+
+/// " -- today", " -- tomorrow", " -- in 19 days"
+pub(crate) fn get_countdown(dtm: NaiveDate, today: NaiveDate) -> String {
+    match (dtm - today).num_days() {
+        0 => " -- today".to_string(),
+        1 => " -- tomorrow".to_string(),
+        n => format!(" -- in {n} days"),
+    }
+}
+
+#[cfg(test)]
+mod test_search {
+    use chrono::NaiveDate;
+
+    use crate::search::{Matcher, SearchMode, get_countdown, get_occurrences, get_search_term};
+
+    #[test]
+    fn search_ignores_case() {
+        let m = Matcher::new("Dentist", SearchMode::Plain, false, false).unwrap();
+        assert!(m.matches("2025 May 17 AT 10:00, dentist", "dentist"));
+        let m = Matcher::new("Dentist", SearchMode::Plain, true, false).unwrap();
+        assert!(!m.matches("2025 May 17 AT 10:00, dentist", "dentist"));
+    }
+
+    #[test]
+    fn search_description_only() {
+        let m = Matcher::new("may", SearchMode::Plain, false, false).unwrap();
+        assert!(!m.matches("2025 May 17 AT 10:00, dentist", "dentist"));
+        let m = Matcher::new("may", SearchMode::Plain, false, true).unwrap();
+        assert!(m.matches("2025 May 17 AT 10:00, dentist", "dentist"));
+    }
+
+    #[test]
+    fn search_regex() {
+        let m = Matcher::new("^dent(ist)?$", SearchMode::Regex, false, false).unwrap();
+        assert!(m.matches("", "Dentist"));
+        assert!(!m.matches("", "Dentists"));
+        assert!(Matcher::new("(", SearchMode::Regex, false, false).is_err());
+    }
+
+    #[test]
+    fn search_fuzzy() {
+        let m = Matcher::new("hbd", SearchMode::Fuzzy, false, false).unwrap();
+        assert!(m.matches("", "Heiko's birthday"));
+        assert!(!m.matches("", "Heiko"));
+        let m = Matcher::new("birth", SearchMode::Fuzzy, false, false).unwrap();
+        assert!(m.score("birthday").unwrap() > m.score("big earth").unwrap());
+    }
+
+    #[test]
+    fn search_term() {
+        let args = |s: &str| s.split(' ').map(|a| a.to_string()).collect::<Vec<String>>();
+        let term = |s: &str| get_search_term(&args(s));
+        assert_eq!(term("rremind when --fuzzy hbd"), Some("hbd".to_string()));
+        assert_eq!(
+            term("rremind when_was --file work dentist --count"),
+            Some("dentist".to_string())
+        );
+        assert_eq!(term("rremind when_was --tag health"), None);
+    }
+
+    #[test]
+    fn search_occurrences() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 17).unwrap(); // a Saturday
+        let dates = |line: &str, horizon: u64, max: usize| {
+            get_occurrences(line, today, horizon, max, None)
+                .iter()
+                .map(|termin| termin.appointment_date.unwrap().format("%m-%d").to_string())
+                .collect::<Vec<String>>()
+        };
+        // weekly: the next occurrences, as many as asked for ...
+        assert_eq!(
+            dates("Mon,Sat, gym", 365, 3),
+            vec!["05-17", "05-19", "05-24"]
+        );
+        // ... within the horizon
+        assert_eq!(dates("Mon,Sat, gym", 3, 5), vec!["05-17", "05-19"]);
+        // yearly: the next one, even if it is next year
+        assert_eq!(dates("may 16, birthday", 365, 1), vec!["05-16"]);
+        assert!(dates("may 16, birthday", 300, 1).is_empty());
+        // full dates, if they are not past
+        assert_eq!(dates("2025-05-17, dentist", 0, 0), vec!["05-17"]);
+        assert!(dates("2025-05-16, dentist", 365, 3).is_empty());
+        assert!(dates("someday, x", 365, 3).is_empty());
+    }
+
+    #[test]
+    fn search_countdown() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 17).unwrap();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 5, d).unwrap();
+        assert_eq!(get_countdown(day(17), today), " -- today");
+        assert_eq!(get_countdown(day(18), today), " -- tomorrow");
+        assert_eq!(get_countdown(day(31), today), " -- in 14 days");
+    }
+}
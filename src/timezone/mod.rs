@@ -0,0 +1,145 @@
+// Time zones: a line can name the zone of its time
+// (`Tue AT 15:00 America/New_York, call with NY`), a .rem-file can
+// set a default (`# tz=Asia/Tokyo`). Listings show these appointments
+// in the zone of rr.rc (`rremind_timezone=Europe/Berlin`, default: the
+// zone of the computer), and the time as written in brackets.
+// The zone data is compiled into rremind (chrono-tz).
+
+use std::sync::OnceLock;
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+use crate::{Appointment, parser::parse_line_leniently};
+
+// The zone of the viewer (set once, in main); None is the computer's zone
+static VIEWER_ZONE: OnceLock<Option<Tz>> = OnceLock::new();
+
+/// Remember the zone configured in rr.rc (empty: the computer's zone)
+pub(crate) fn set_viewer_zone(zone: &str) {
+    let tz = match zone.trim() {
+        "" => None,
+        name => {
+            let tz = parse_zone(name);
+            if tz.is_none() {
+                eprintln!("Sorry, I do not know the time zone `{name}` (try e.g. Europe/Berlin).");
+            }
+            tz
+        }
+    };
+    let _ = VIEWER_ZONE.set(tz);
+}
+
+/// "America/New_York", "asia/tokyo", "UTC" => Some(Tz)
+pub(crate) fn parse_zone(word: &str) -> Option<Tz> {
+    // Zone names have a slash, except for a few such as UTC
+    if !word.contains('/') && !word.eq_ignore_ascii_case("utc") {
+        return None;
+    }
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .find(|tz| tz.name().eq_ignore_ascii_case(word))
+        .copied()
+}
+
+/// The zone named in the date part of the line
+pub(crate) fn extract_zone(s_in: &str) -> Option<Tz> {
+    parse_line_leniently(s_in).ok()?.zone
+}
+
+/// Appointment in the zone of the viewer: date, start and stop are
+/// converted, the time as written is kept for the listing.
+/// Appointments without a time are left as they are.
+pub(crate) fn to_viewer_zone(termin: &mut Appointment, zone: Tz) {
+    to_zone(termin, zone, VIEWER_ZONE.get().copied().flatten());
+}
+
+fn to_zone(termin: &mut Appointment, zone: Tz, viewer: Option<Tz>) {
+    let (Some(datum), Some(start)) = (termin.appointment_date, termin.appointment_start) else {
+        return;
+    };
+    if Some(zone) == viewer {
+        return;
+    }
+    let Some(converted) = convert(datum.and_time(start), zone, viewer) else {
+        return;
+    };
+    if converted == datum.and_time(start) {
+        return;
+    }
+    termin.appointment_date = Some(converted.date());
+    termin.appointment_start = Some(converted.time());
+    termin.appointment_stop = termin
+        .appointment_stop
+        .and_then(|stop| convert(datum.and_time(stop), zone, viewer))
+        .map(|stop| stop.time());
+    termin.appointment_zone = Some((zone, start));
+}
+
+fn convert(datetime: NaiveDateTime, zone: Tz, viewer: Option<Tz>) -> Option<NaiveDateTime> {
+    let in_zone = zone.from_local_datetime(&datetime).earliest()?;
+    Some(match viewer {
+        Some(tz) => in_zone.with_timezone(&tz).naive_local(),
+        None => in_zone.with_timezone(&Local).naive_local(),
+    })
+}
+
+/// Dates in the zone of the line that may end up on `datum` for the viewer
+pub(crate) fn get_candidate_dates(datum: NaiveDate) -> Vec<NaiveDate> {
+    [datum.pred_opt(), Some(datum), datum.succ_opt()]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod test_timezone {
+    use chrono::{NaiveDate, NaiveTime};
+    use chrono_tz::Tz;
+
+    use super::{extract_zone, parse_zone, to_zone};
+    use crate::parser::get_termin_from_line;
+
+    #[test]
+    fn timezone_parse() {
+        assert_eq!(parse_zone("America/New_York"), Some(Tz::America__New_York));
+        assert_eq!(parse_zone("asia/tokyo"), Some(Tz::Asia__Tokyo));
+        assert_eq!(parse_zone("UTC"), Some(Tz::UTC));
+        assert!(parse_zone("dentist").is_none());
+        assert!(parse_zone("and/or").is_none());
+        assert_eq!(
+            extract_zone("Tue AT 15:00 America/New_York, call"),
+            Some(Tz::America__New_York)
+        );
+        assert!(extract_zone("Tue AT 15:00, call with America/New_York").is_none());
+    }
+
+    #[test]
+    fn timezone_convert() {
+        let line = "2025 jun 3 AT 15:00-16:00 America/New_York, call";
+        let mut termin = get_termin_from_line(line, None).unwrap();
+        to_zone(&mut termin, Tz::America__New_York, Some(Tz::Europe__Berlin));
+        assert_eq!(termin.appointment_date, NaiveDate::from_ymd_opt(2025, 6, 3));
+        assert_eq!(termin.appointment_start, NaiveTime::from_hms_opt(21, 0, 0));
+        assert_eq!(termin.appointment_stop, NaiveTime::from_hms_opt(22, 0, 0));
+        assert_eq!(
+            termin.appointment_zone,
+            Some((
+                Tz::America__New_York,
+                NaiveTime::from_hms_opt(15, 0, 0).unwrap()
+            ))
+        );
+
+        // 7:00 in Tokyo is the evening before in Berlin
+        let line = "2025 jan 10 AT 7:00 Asia/Tokyo, standup";
+        let mut termin = get_termin_from_line(line, None).unwrap();
+        to_zone(&mut termin, Tz::Asia__Tokyo, Some(Tz::Europe__Berlin));
+        assert_eq!(termin.appointment_date, NaiveDate::from_ymd_opt(2025, 1, 9));
+        assert_eq!(termin.appointment_start, NaiveTime::from_hms_opt(23, 0, 0));
+
+        // Same zone: nothing to convert
+        let mut termin = get_termin_from_line(line, None).unwrap();
+        to_zone(&mut termin, Tz::Asia__Tokyo, Some(Tz::Asia__Tokyo));
+        assert!(termin.appointment_zone.is_none());
+    }
+}
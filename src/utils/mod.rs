@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufWriter, Write};
 use std::{
     fs::{self, OpenOptions},
     path::Path,
@@ -7,30 +7,65 @@ use std::{
 
 use crate::{get_rremind_folders, get_user_input};
 
-pub(crate) fn remove_line_from_file(file_name: &str, line: &str) {
-    //Scope to ensure that the files are closed
+/// All lines of `file_name`; empty if the file does not exist (yet)
+pub(crate) fn read_lines_from_file(file_name: &str) -> Vec<String> {
+    match fs::read_to_string(file_name) {
+        Ok(contents) => contents.lines().map(|line| line.to_string()).collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Replace the contents of `file_name` with `lines`. This writes a
+/// temporary file first and then moves it over the original.
+pub(crate) fn write_lines_to_file(file_name: &str, lines: &[String]) {
     let s_tmp_file_path = format!("{}.tmp", &file_name);
-    let file: File =
-        File::open(&file_name).expect(&format!("Cannot open rremind-file `{}`", &file_name));
     let out_file: File = File::create(&s_tmp_file_path)
-        .expect(&format!("Cannot create tmp-file `{}`", &s_tmp_file_path));
-
-    let reader = BufReader::new(&file);
+        .unwrap_or_else(|_| panic!("Cannot create tmp-file `{s_tmp_file_path}`"));
     let mut writer = BufWriter::new(&out_file);
+    for single_line in lines {
+        writeln!(writer, "{}", single_line).unwrap_or_default();
+    }
+    writer
+        .flush()
+        .unwrap_or_else(|_| panic!("Cannot write tmp-file `{s_tmp_file_path}`"));
+    fs::rename(&s_tmp_file_path, file_name).unwrap_or_else(|_| panic!("Cannot replace original file `{file_name}` with temporary (new) version `{s_tmp_file_path}`"));
+}
 
-    for sline in reader.lines() {
-        let single_line = sline.as_ref().unwrap();
-        if single_line != line {
-            writeln!(writer, "{}", single_line).unwrap_or_default();
+/// Replace line `line_no` (1-based) of `file_name` with `new_line`, or
+/// remove it if `new_line` is None -- but only if it still reads
+/// `old_line`. Like `write_lines_to_file`, through a temporary file.
+pub(crate) fn replace_line_in_file(
+    file_name: &str,
+    line_no: usize,
+    old_line: &str,
+    new_line: Option<&str>,
+) -> Result<(), String> {
+    let mut lines = read_lines_from_file(file_name);
+    if line_no == 0 || lines.get(line_no - 1).map(|line| line.as_str()) != Some(old_line) {
+        return Err(format!(
+            "Line {line_no} of {file_name} has changed in the meantime; nothing was written."
+        ));
+    }
+    match new_line {
+        Some(line) => lines[line_no - 1] = line.to_string(),
+        None => {
+            lines.remove(line_no - 1);
         }
     }
-    fs::rename(&s_tmp_file_path, file_name).expect(&format!("Cannot replace original rremind file `{file_name}` with temporary (new) version `{s_tmp_file_path}`"));
+    write_lines_to_file(file_name, &lines);
+    Ok(())
 }
 
+/// Append `s_line` as a line of its own (also if the file does not end
+/// with a line break)
 pub(crate) fn append_line_to_file(s_file: &str, s_line: &str) -> bool {
+    let ends_with_newline = fs::read_to_string(s_file).map_or(true, |contents| {
+        contents.is_empty() || contents.ends_with('\n')
+    });
     let mut file = OpenOptions::new().append(true).open(s_file).unwrap();
+    let separator = if ends_with_newline { "" } else { "\n" };
 
-    if let Err(e) = write!(file, "\n{}", s_line) {
+    if let Err(e) = writeln!(file, "{separator}{s_line}") {
         eprintln!("Couldn't write to file: {}", e);
         return false;
     };
@@ -38,18 +73,32 @@ pub(crate) fn append_line_to_file(s_file: &str, s_line: &str) -> bool {
     true
 }
 
-pub(crate) fn ask_option(question: &str, options: Vec<String>) -> String {
+/// Value following a flag on the command line, e.g.
+/// `rremind when_was dentist --file work` => `get_flag_value(args, "--file")` is Some("work")
+pub(crate) fn get_flag_value(args: &[String], flag: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).map(|value| value.to_owned())
+}
+
+/// Ask a yes/no question; an empty answer is `default`
+pub(crate) fn confirm(question: &str, default: bool) -> bool {
+    let answer = get_user_input(question, if default { "y" } else { "n" });
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Let the user pick one of `options` by its number (or its name);
+/// None if the answer is neither
+pub(crate) fn ask_option(question: &str, options: Vec<String>) -> Option<String> {
     for (index, option) in options.iter().enumerate() {
         println!("{index} -- {option}");
     }
     println!("");
     let s = get_user_input(question, &"").trim().to_lowercase();
     match s.parse::<usize>() {
-        Ok(u) => match options.get(u) {
-            Some(response) => return response.to_string(),
-            None => panic!("Not understood, @todo needs programming"),
-        },
-        Err(e) => panic!("@todo, needs programming {e}"),
+        Ok(u) => options.get(u).cloned(),
+        Err(_) => options
+            .into_iter()
+            .find(|option| option.to_lowercase() == s),
     }
 }
 